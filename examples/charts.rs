@@ -0,0 +1,71 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use bevy_picoui::{
+    palette::RGB_PALETTE,
+    pico::{ItemStyle, Pico, Pico2dCamera, PicoItem},
+    widgets::{bar_chart, sparkline},
+    PicoPlugin,
+};
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PicoPlugin::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, update)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), Pico2dCamera));
+}
+
+fn update(mut pico: ResMut<Pico>, time: Res<Time>, mut frame_times: Local<Vec<f32>>) {
+    frame_times.push(time.delta_seconds() * 1000.0);
+    if frame_times.len() > 120 {
+        frame_times.remove(0);
+    }
+
+    let panel_style = ItemStyle {
+        background_color: Color::rgb(0.1, 0.1, 0.1),
+        corner_radius: Val::Vh(1.0),
+        ..default()
+    };
+
+    // Frame time graph, fixed range so spikes stand out
+    let frame_graph = sparkline(
+        &mut pico,
+        PicoItem {
+            x: Val::Percent(50.0),
+            y: Val::Percent(30.0),
+            width: Val::Vh(70.0),
+            height: Val::Vh(20.0),
+            anchor: Anchor::Center,
+            style: panel_style.clone(),
+            ..default()
+        },
+        &frame_times,
+        RGB_PALETTE[1][2],
+        Some(0.0..=33.3),
+    );
+    pico.get_mut(&frame_graph).style.tooltip = frame_times
+        .last()
+        .map(|ms| format!("{ms:.1}ms, the graph is 0 to 33ms"));
+
+    let bars = pico.add(PicoItem {
+        x: Val::Percent(50.0),
+        y: Val::Percent(70.0),
+        width: Val::Vh(70.0),
+        height: Val::Vh(30.0),
+        anchor: Anchor::Center,
+        style: panel_style,
+        ..default()
+    });
+    let values = [3.0, 7.0, -2.0, 5.0, 9.0, -4.0, 6.0, 1.0];
+    let colors = [RGB_PALETTE[0][1], RGB_PALETTE[0][3]];
+    let bar_indices = bar_chart(&mut pico, bars, &values, Some(&colors), None);
+    for (bar, value) in bar_indices.iter().zip(values) {
+        pico.get_mut(bar).style.tooltip = Some(format!("{value}"));
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use bevy_picoui::{
+    pico::{LayoutNode, Pico, Pico2dCamera},
+    PicoPlugin,
+};
+
+// The nine_patch example menu, described in data instead of code
+const MENU: &str = r#"(
+    name: Some("blue_panel"),
+    item: (
+        x: Percent(50.0),
+        y: Percent(50.0),
+        width: Vh(70.0),
+        height: Vh(50.0),
+        anchor: Center,
+        style: (
+            background_color: Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+            nine_patch: Some((7, 7, 7, 7)),
+        ),
+    ),
+    children: [
+        (
+            name: Some("title"),
+            item: (
+                text: "SUPER AWESOME GAME",
+                width: Percent(100.0),
+                height: Percent(12.0),
+                anchor: TopCenter,
+                anchor_parent: TopCenter,
+                style: (font_size: Vh(2.5)),
+            ),
+        ),
+        (
+            name: Some("grey_panel"),
+            item: (
+                width: Percent(100.0),
+                height: Percent(88.0),
+                anchor: BottomCenter,
+                anchor_parent: BottomCenter,
+                style: (
+                    background_color: Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+                    nine_patch: Some((7, 7, 7, 7)),
+                ),
+            ),
+            children: [
+                (
+                    name: Some("start"),
+                    item: (
+                        text: "START GAME",
+                        y: Percent(15.0),
+                        width: Percent(70.0),
+                        height: Percent(22.0),
+                        anchor: TopCenter,
+                        anchor_parent: TopCenter,
+                        style: (
+                            font_size: Vh(2.5),
+                            text_color: Rgba(red: 0.3, green: 0.3, blue: 0.3, alpha: 1.0),
+                            background_color: Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+                            nine_patch: Some((7, 7, 7, 28)),
+                        ),
+                    ),
+                ),
+                (
+                    name: Some("options"),
+                    item: (
+                        text: "OPTIONS",
+                        y: Percent(42.0),
+                        width: Percent(70.0),
+                        height: Percent(17.0),
+                        anchor: TopCenter,
+                        anchor_parent: TopCenter,
+                        style: (
+                            font_size: Vh(2.5),
+                            background_color: Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+                            nine_patch: Some((7, 7, 7, 28)),
+                        ),
+                    ),
+                ),
+                (
+                    name: Some("credits"),
+                    item: (
+                        text: "CREDITS",
+                        y: Percent(64.0),
+                        width: Percent(70.0),
+                        height: Percent(17.0),
+                        anchor: TopCenter,
+                        anchor_parent: TopCenter,
+                        style: (
+                            font_size: Vh(2.5),
+                            background_color: Rgba(red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0),
+                            nine_patch: Some((7, 7, 7, 28)),
+                        ),
+                    ),
+                ),
+            ],
+        ),
+    ],
+)"#;
+
+fn main() {
+    App::new()
+        .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PicoPlugin::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, update)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), Pico2dCamera));
+}
+
+fn update(
+    mut pico: ResMut<Pico>,
+    asset_server: Res<AssetServer>,
+    mut layout: Local<Option<LayoutNode>>,
+) {
+    let layout = layout.get_or_insert_with(|| ron::from_str(MENU).unwrap());
+    let items = pico.add_tree(layout);
+
+    // Handles aren't part of the data, look the items up by name to set them
+    let image = |path: &str| Some(asset_server.load(format!("kenney_ui-pack/PNG/{path}.png")));
+    let font: Handle<Font> = asset_server.load("kenney_ui-pack/Font/kenvector_future.ttf");
+    pico.get_mut(&items["blue_panel"]).style.image = image("blue_panel");
+    pico.get_mut(&items["grey_panel"]).style.image = image("grey_panel");
+    pico.get_mut(&items["title"]).style.font = font.clone();
+    for (name, color) in [
+        ("start", "yellow"),
+        ("options", "blue"),
+        ("credits", "blue"),
+    ] {
+        let button = items[name];
+        let state = if pico.hovered(&button) { 8 } else { 7 };
+        let style = &mut pico.get_mut(&button).style;
+        style.image = image(&format!("{color}_button0{state}"));
+        style.font = font.clone();
+        if pico.clicked(&button) {
+            info!("{name} clicked");
+        }
+    }
+}
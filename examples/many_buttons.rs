@@ -114,6 +114,7 @@ fn spawn_button(
             border_color,
             border_width: border,
             font_size: Val::Px(FONT_SIZE),
+            crisp_text: true,
             ..default()
         },
         parent: Some(parent.clone()),
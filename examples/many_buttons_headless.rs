@@ -0,0 +1,179 @@
+//! Runs the many_buttons layout without a window or GPU and reports the cost of `render`, and how many
+//! `RectangleMaterial`s are added each frame.
+//! A row of buttons is highlighted each frame, like a hover sweeping down the grid, so some items are
+//! regenerated every frame.
+//!
+//! `cargo run --example many_buttons_headless --release`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    input::InputPlugin, prelude::*, render::render_resource::Shader, sprite::Anchor,
+    window::PrimaryWindow,
+};
+
+use bevy_picoui::{
+    pico::{ItemIndex, ItemStyle, Pico, Pico2dCamera, PicoItem},
+    rectangle_material::RectangleMaterial,
+    PicoPlugin,
+};
+
+// For a total of 110 * 110 = 12100 buttons with text
+const ROW_COLUMN_COUNT: usize = 110;
+const FONT_SIZE: f32 = 7.0;
+const WARMUP_FRAMES: usize = 10;
+const FRAMES: usize = 100;
+
+/// Counts heap allocations so the reported number doesn't depend on the allocator
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+        InputPlugin,
+    ))
+    // Normally added by the render and sprite plugins
+    .init_asset::<Shader>()
+    .init_asset::<Mesh>()
+    .init_asset::<Image>()
+    .init_asset::<ColorMaterial>()
+    .init_asset::<Font>()
+    .add_plugins(PicoPlugin::default())
+    .init_resource::<MaterialsAdded>()
+    .add_systems(Startup, setup)
+    .add_systems(Update, update)
+    .add_systems(Last, count_materials);
+
+    let mut total_time = Duration::ZERO;
+    let mut total_allocations = 0;
+    let mut total_bytes = 0;
+    let mut materials_at_start = 0;
+    for frame in 0..WARMUP_FRAMES + FRAMES {
+        if frame == WARMUP_FRAMES {
+            materials_at_start = app.world.resource::<MaterialsAdded>().0;
+        }
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+        let bytes = ALLOCATED_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+        app.update();
+        if frame >= WARMUP_FRAMES {
+            total_time += start.elapsed();
+            total_allocations += ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+            total_bytes += ALLOCATED_BYTES.load(Ordering::Relaxed) - bytes;
+        }
+    }
+    let materials_added = app.world.resource::<MaterialsAdded>().0 - materials_at_start;
+    println!(
+        "{FRAMES} frames: {:.2}ms, {} allocations, {:.1}MB allocated, {:.1} materials added per frame",
+        total_time.as_secs_f64() * 1000.0 / FRAMES as f64,
+        total_allocations / FRAMES,
+        total_bytes as f64 / FRAMES as f64 / 1e6,
+        materials_added as f64 / FRAMES as f64,
+    );
+}
+
+#[derive(Resource, Default)]
+struct MaterialsAdded(usize);
+
+fn count_materials(
+    mut events: EventReader<AssetEvent<RectangleMaterial>>,
+    mut added: ResMut<MaterialsAdded>,
+) {
+    added.0 += events
+        .read()
+        .filter(|event| matches!(event, AssetEvent::Added { .. }))
+        .count();
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Window::default(), PrimaryWindow));
+    commands.spawn((Camera2dBundle::default(), Pico2dCamera));
+}
+
+fn update(mut pico: ResMut<Pico>, mut frame: Local<usize>) {
+    let count = ROW_COLUMN_COUNT;
+    let count_f = count as f32;
+    let as_rainbow = |i: usize| Color::hsl((i as f32 / count_f) * 360.0, 0.9, 0.8);
+    let highlighted_row = *frame % count;
+    *frame += 1;
+
+    let main_box = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        anchor: Anchor::Center,
+        anchor_parent: Anchor::Center,
+        ..default()
+    });
+
+    for i in 0..count {
+        for j in 0..count {
+            let color = if i == highlighted_row {
+                Color::ORANGE_RED
+            } else {
+                as_rainbow(j % i.max(1))
+            };
+            let border_color = as_rainbow(i % j.max(1));
+            spawn_button(&mut pico, color, count_f, i, j, border_color, main_box);
+        }
+    }
+}
+
+fn spawn_button(
+    pico: &mut Pico,
+    background_color: Color,
+    total: f32,
+    i: usize,
+    j: usize,
+    border_color: Color,
+    parent: ItemIndex,
+) {
+    let width = 90.0 / total;
+    pico.add(PicoItem {
+        text: format!("{i}, {j}"),
+        x: Val::Percent(100.0 / total * j as f32),
+        y: Val::Percent(100.0 - 100.0 / total * i as f32),
+        width: Val::Percent(width),
+        height: Val::Percent(width),
+        anchor: Anchor::Center,
+        style: ItemStyle {
+            background_color,
+            border_color,
+            border_width: Val::Px(2.0),
+            font_size: Val::Px(FONT_SIZE),
+            crisp_text: true,
+            ..default()
+        },
+        parent: Some(parent),
+        ..default()
+    });
+}
@@ -118,29 +118,27 @@ fn update(
             background_color: Color::WHITE,
             nine_patch: Some(nine_patch.grey_panel.0),
             image: Some(nine_patch.grey_panel.1.clone_weak()),
+            // The buttons are laid out within the middle 70% of the panel
+            padding: (
+                Val::Percent(15.0),
+                Val::Percent(15.0),
+                Val::Percent(15.0),
+                Val::Percent(15.0),
+            ),
             ..default()
         },
         ..default()
     });
 
-    let main_panel = pico.add(PicoItem {
-        width: Val::Percent(70.0),
-        height: Val::Percent(70.0),
-        anchor: Anchor::Center,
-        anchor_parent: Anchor::Center,
-        parent: Some(grey_panel),
-        ..default()
-    });
-
     {
-        let _guard = pico.vstack(Val::Percent(5.0), Val::Percent(5.0), false, &main_panel);
+        let _guard = pico.vstack(Val::Percent(5.0), Val::Percent(5.0), false, &grey_panel);
 
         let btn_template = PicoItem {
             width: Val::Percent(100.0),
             height: Val::Percent(25.0),
             anchor: Anchor::TopCenter,
             anchor_parent: Anchor::TopCenter,
-            parent: Some(main_panel),
+            parent: Some(grey_panel),
             style: ItemStyle {
                 font: font.clone_weak(),
                 font_size: Val::Vh(2.5),
@@ -1,4 +1,4 @@
-use bevy::{input::mouse::MouseWheel, prelude::*, sprite::Anchor};
+use bevy::{prelude::*, sprite::Anchor};
 
 use bevy_picoui::{
     palette::RGB_PALETTE,
@@ -21,13 +21,14 @@ fn setup(mut commands: Commands) {
     commands.spawn((Camera2dBundle::default(), Pico2dCamera));
 }
 
-fn update(mut pico: ResMut<Pico>, mut mouse_wheel_events: EventReader<MouseWheel>) {
+fn update(mut pico: ResMut<Pico>) {
     let indices: Vec<_> = (0..3).flat_map(|i| (0..7).map(move |j| (i, j))).collect();
     let total_items = 3 * 7;
     let max_items_to_show = 10;
     let scroll_range = total_items - max_items_to_show;
 
     let scroll_container = pico.add(PicoItem {
+        x: Val::Vh(-27.0),
         width: Val::Vh(50.0),
         height: Val::Vh(50.0),
         anchor: Anchor::Center,
@@ -47,7 +48,6 @@ fn update(mut pico: ResMut<Pico>, mut mouse_wheel_events: EventReader<MouseWheel
         098743542350897,
         scroll_container,
         None,
-        &mut mouse_wheel_events,
     );
 
     pico.get_mut(&scroll.handle).style = ItemStyle {
@@ -83,4 +83,65 @@ fn update(mut pico: ResMut<Pico>, mut mouse_wheel_events: EventReader<MouseWheel
         item.style.corner_radius = Val::Percent(30.0);
         item.style.background_color = color;
     }
+
+    nested(&mut pico);
+}
+
+/// An outer list of palette rows, each with an inner list of its colors. The wheel scrolls the inner
+/// list under the cursor until it reaches its end, then the outer list.
+fn nested(pico: &mut Pico) {
+    let rows_to_show = 2;
+    let colors_to_show = 3;
+
+    let outer_container = pico.add(PicoItem {
+        x: Val::Vh(27.0),
+        width: Val::Vh(50.0),
+        height: Val::Vh(50.0),
+        anchor: Anchor::Center,
+        anchor_parent: Anchor::Center,
+        style: ItemStyle {
+            background_color: Color::rgb(0.1, 0.1, 0.1),
+            ..default()
+        },
+        ..default()
+    });
+
+    let outer = ScrollAreaWidget::new(
+        pico,
+        RGB_PALETTE.len() as i32 - rows_to_show,
+        rows_to_show,
+        2340958723409857,
+        outer_container,
+        None,
+    );
+
+    for (k, row) in outer.items.iter().enumerate() {
+        let i = k + outer.position as usize;
+        let inner_container = pico.add(PicoItem {
+            width: Val::Percent(90.0),
+            height: Val::Percent(90.0),
+            anchor: Anchor::Center,
+            anchor_parent: Anchor::Center,
+            style: ItemStyle {
+                background_color: Color::rgb(0.2, 0.2, 0.2),
+                ..default()
+            },
+            parent: Some(*row),
+            ..default()
+        });
+        let inner = ScrollAreaWidget::new(
+            pico,
+            RGB_PALETTE[i].len() as i32 - colors_to_show,
+            colors_to_show,
+            // Ids need to be consistent and unique for each inner list
+            9823475029834 + i as u64 * 2,
+            inner_container,
+            None,
+        );
+        for (l, index) in inner.items.iter().enumerate() {
+            let item = pico.get_mut(index);
+            item.style.corner_radius = Val::Percent(30.0);
+            item.style.background_color = RGB_PALETTE[i][l + inner.position as usize];
+        }
+    }
 }
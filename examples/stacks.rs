@@ -73,6 +73,51 @@ fn update(mut pico: ResMut<Pico>, mut toggle_states: Local<[[bool; 10]; 10]>) {
         }
     }
 
+    // Rows wrap to fit the box, tags are sized to their text
+    let tag_box = pico.add(PicoItem {
+        y: Val::Percent(77.0),
+        x: Val::Percent(50.0),
+        width: Val::Vh(70.0),
+        height: Val::Vh(16.0),
+        anchor: Anchor::TopCenter,
+        ..default()
+    });
+    {
+        let tags = [
+            "rust",
+            "bevy",
+            "immediate mode",
+            "ui",
+            "stacks",
+            "wrapping",
+            "tags",
+            "layout",
+            "gpu",
+            "shaders",
+            "text",
+            "buttons",
+            "responsive",
+            "grid",
+            "cloud",
+        ];
+        let _guard = pico.wrap_hstack(Val::Px(0.0), Val::Vh(1.0), Val::Vh(4.0), &tag_box);
+        for (i, tag) in tags.iter().enumerate() {
+            pico.add(PicoItem {
+                text: tag.to_string(),
+                width: Val::Vh(tag.len() as f32 * 1.2 + 2.0),
+                height: Val::Vh(4.0),
+                anchor: Anchor::TopLeft,
+                style: ItemStyle {
+                    corner_radius: Val::Percent(50.0),
+                    background_color: RGB_PALETTE[2][i % RGB_PALETTE[2].len()] * 0.6,
+                    ..default()
+                },
+                parent: Some(tag_box),
+                ..default()
+            });
+        }
+    }
+
     let top_down_box = pico.add(PicoItem {
         y: Val::Percent(10.0),
         x: Val::Percent(10.0),
@@ -3,7 +3,7 @@ use bevy::{math::*, prelude::*, render::view::RenderLayers, sprite::Anchor};
 use bevy_basic_camera::{CameraController, CameraControllerPlugin};
 use bevy_coordinate_systems::CoordinateTransformationsPlugin;
 use bevy_picoui::{
-    pico::{ItemStyle, Pico, Pico2dCamera, PicoItem},
+    pico::{DistanceScale, ItemStyle, Pico, Pico2dCamera, PicoItem},
     PicoPlugin,
 };
 
@@ -87,6 +87,8 @@ fn update(mut gizmos: Gizmos, mut pico: ResMut<Pico>) {
         text: String::from("Click and drag to orbit camera\nDolly with scroll wheel\nMove with WASD\n\nHover over the Y axis text"),
         style: ItemStyle {
             anchor_text: Anchor::TopLeft,
+            // Readable over the scene without a background
+            text_outline: Some((Color::BLACK, Val::Px(1.5))),
             ..default()
         },
         ..default()
@@ -98,6 +100,11 @@ fn update(mut gizmos: Gizmos, mut pico: ResMut<Pico>) {
     // Add 3d text
     let axis_text_index = pico.add(PicoItem {
         position_3d: Some(Vec3::Y * 1.1),
+        // Gets smaller when dollying away, the same size as without scaling from the start position
+        scale_with_distance: Some(DistanceScale {
+            reference_distance: 11.0,
+            ..default()
+        }),
         uv_size: vec2(0.02, 0.02),
         style: ItemStyle {
             background_color: Color::rgba(0.1, 0.1, 0.1, 0.5),
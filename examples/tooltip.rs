@@ -37,6 +37,37 @@ fn update(mut pico: ResMut<Pico>, windows: Query<&Window>) {
         return;
     };
 
+    // Help text with clickable links
+    let link_color = RGB_PALETTE[0][4];
+    let help = pico.add(PicoItem {
+        sections: vec![
+            ("Colors are from the ".to_string(), Color::WHITE),
+            ("palette module".to_string(), link_color),
+            (", see also the ".to_string(), Color::WHITE),
+            ("bevy docs".to_string(), link_color),
+        ],
+        y: Val::Vh(2.0),
+        width: Val::Percent(100.0),
+        height: Val::Vh(5.0),
+        anchor: Anchor::TopLeft,
+        anchor_parent: Anchor::BottomLeft,
+        parent: Some(main_box),
+        ..default()
+    });
+    let urls = [
+        "https://docs.rs/bevy_picoui/latest/bevy_picoui/palette",
+        "https://docs.rs/bevy",
+    ];
+    let links = pico.text_links(&help, &[1, 3]);
+    for ((link, span), url) in links.iter().zip([1, 3]).zip(urls) {
+        if pico.hovered(link) {
+            pico.get_mut(&help).sections[span].1 = Color::WHITE;
+        }
+        if pico.clicked(link) {
+            info!("open {url}");
+        }
+    }
+
     {
         let _guard = pico.vstack(Val::Px(0.0), Val::Px(0.0), false, &main_box);
 
@@ -78,11 +109,18 @@ fn update(mut pico: ResMut<Pico>, windows: Query<&Window>) {
                         });
                         if pico.hovered(&btn) {
                             if let Some(cursor_position) = window.cursor_position() {
+                                let label = format!("{:#?}", color);
+                                // Fit the tooltip to the label
+                                let text_size = pico.measure_text(
+                                    &label,
+                                    &Handle::default(),
+                                    ItemStyle::default().font_size,
+                                );
                                 let tooltip = pico.add(PicoItem {
                                     x: Val::Px(cursor_position.x + 15.0),
                                     y: Val::Px(cursor_position.y + 20.0),
-                                    width: Val::Vh(20.0),
-                                    height: Val::Vh(15.0),
+                                    width: Val::Px(text_size.x + 20.0),
+                                    height: Val::Px(text_size.y + 20.0),
                                     style: ItemStyle {
                                         background_color: color,
                                         border_color: Color::WHITE,
@@ -102,7 +140,7 @@ fn update(mut pico: ResMut<Pico>, windows: Query<&Window>) {
                                 let mut text = PicoItem {
                                     x: Val::Px(1.0),
                                     y: Val::Px(1.0),
-                                    text: format!("{:#?}", color),
+                                    text: label,
                                     anchor_parent: Anchor::Center,
                                     style: ItemStyle {
                                         justify: JustifyText::Left,
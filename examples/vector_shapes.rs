@@ -5,7 +5,7 @@ use bevy::{math::*, prelude::*, sprite::Anchor};
 use bevy_picoui::{
     palette::RGB_PALETTE,
     pico::{ItemStyle, Pico, Pico2dCamera, PicoItem},
-    widgets::drag_value,
+    widgets::{drag_value, CharFilter},
     PicoPlugin,
 };
 use bevy_vector_shapes::{prelude::ShapePainter, shapes::*, Shape2dPlugin};
@@ -116,6 +116,7 @@ fn update(
             values[i],
             drag_index,
             Some(&mut char_input_events),
+            CharFilter::Numeric,
         )
         .clamp(0.0, 1.0);
 
@@ -3,10 +3,12 @@ use bevy::{
     input::InputSystem,
     prelude::*,
     sprite::{Material2d, Mesh2dHandle},
+    text::update_text2d_layout,
+    transform::TransformSystem,
 };
-use pico::{MaterialHandleEntity, Pico};
+use pico::{MaterialHandleEntity, Pico, PicoInstance};
 use rectangle_material::RectangleMaterialPlugin;
-use renderer::render;
+use renderer::{apply_theme, render, update_text_spans};
 use std::marker::PhantomData;
 
 pub mod guard;
@@ -32,7 +34,18 @@ impl Plugin for PicoPlugin {
             .init_resource::<Pico>()
             .add_systems(
                 PreUpdate,
-                (render.after(InputSystem), apply_deferred).chain(),
+                (
+                    apply_theme::<Pico>,
+                    render::<Pico>.after(InputSystem),
+                    apply_deferred,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                update_text_spans::<Pico>
+                    .after(update_text2d_layout)
+                    .after(TransformSystem::TransformPropagate),
             )
             .add_systems(Startup, setup);
         if let Some(n) = self.create_default_2d_cam_with_order {
@@ -45,11 +58,20 @@ impl Plugin for PicoPlugin {
 #[derive(Resource)]
 pub struct MeshHandles {
     rect: Handle<Mesh>,
+    // White, the color comes from the vertex colors of instanced items.
+    instanced_material: Handle<ColorMaterial>,
 }
 
-fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+) {
     let rect: Mesh2dHandle = meshes.add(Rectangle::new(1.0, 1.0)).into();
-    commands.insert_resource(MeshHandles { rect: rect.0 });
+    commands.insert_resource(MeshHandles {
+        rect: rect.0,
+        instanced_material: color_materials.add(Color::WHITE),
+    });
 }
 
 fn setup_2d_camera(mut commands: Commands, order: Res<CreateDefaultCamWithOrder>) {
@@ -63,6 +85,37 @@ fn setup_2d_camera(mut commands: Commands, order: Res<CreateDefaultCamWithOrder>
     });
 }
 
+/// Adds a separate `PicoInstance<M>` resource rendered to cameras with `PicoCamera<M>`.
+/// Its state and interaction are independent from the default `Pico`. `PicoPlugin` is also required.
+pub struct PicoInstancePlugin<M: Send + Sync + 'static>(PhantomData<M>);
+
+impl<M: Send + Sync + 'static> Default for PicoInstancePlugin<M> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<M: Send + Sync + 'static> Plugin for PicoInstancePlugin<M> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PicoInstance<M>>()
+            .add_systems(
+                PreUpdate,
+                (
+                    apply_theme::<PicoInstance<M>>,
+                    render::<PicoInstance<M>>.after(InputSystem),
+                    apply_deferred,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                update_text_spans::<PicoInstance<M>>
+                    .after(update_text2d_layout)
+                    .after(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
 pub struct PicoMaterialPlugin<M: Material2d>(PhantomData<M>);
 
 impl<M: Material2d> Default for PicoMaterialPlugin<M> {
@@ -74,7 +127,7 @@ impl<M: Material2d> Default for PicoMaterialPlugin<M> {
 impl<M: Material2d> Plugin for PicoMaterialPlugin<M> {
     fn build(&self, app: &mut App) {
         app.init_resource::<Pico>()
-            .add_systems(PreUpdate, insert_custom_material::<M>.after(render));
+            .add_systems(PreUpdate, insert_custom_material::<M>.after(render::<Pico>));
     }
 }
 
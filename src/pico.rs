@@ -1,33 +1,106 @@
+use ab_glyph::{Font as _, FontArc, ScaleFont};
 use bevy::{
+    a11y::accesskit::Role,
     ecs::system::SystemParam,
-    math::{vec2, vec4, Vec4Swizzles},
+    math::{vec2, vec4, Affine3A, Vec4Swizzles},
     prelude::*,
-    render::render_resource::BlendState,
+    render::{
+        primitives::Aabb,
+        render_asset::RenderAssetUsages,
+        render_resource::{BlendState, Extent3d, TextureDimension},
+    },
     sprite::{Anchor, Material2d},
-    utils::{label::DynHash, HashMap},
+    utils::{label::DynHash, HashMap, HashSet},
 };
 use core::hash::Hash;
 use core::hash::Hasher;
-use std::collections::hash_map::DefaultHasher;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    marker::PhantomData,
+};
 
 use crate::{
     guard::Guard,
     hash::{hash_anchor, hash_color, hash_val, hash_vec2, hash_vec3, hash_vec4},
-    rectangle_material::{RectangleMaterial, RectangleMaterialUniform},
+    rectangle_material::{
+        RectangleMaterial, RectangleMaterialUniform, MATERIAL_FLAGS_BACKDROP_BLUR_BIT,
+        MATERIAL_FLAGS_CLIP_BIT, MATERIAL_FLAGS_DASHED_BORDER_BIT,
+        MATERIAL_FLAGS_DOTTED_BORDER_BIT, MATERIAL_FLAGS_PIXEL_PERFECT_BIT,
+        MATERIAL_FLAGS_PREMULTIPLIED_BIT, MATERIAL_FLAGS_RADIAL_GRADIENT_BIT,
+        MATERIAL_FLAGS_TEXTURE_BIT, MAX_GRADIENT_STOPS,
+    },
     renderer::MAJOR_DEPTH_AUTO_STEP,
 };
 
 #[derive(Clone, Copy, Debug, Hash)]
 pub struct ItemIndex(pub usize);
 
-// Only supports one camera.
+/// Camera marker for the default `Pico` resource. Only one camera can have it, use a `PicoInstance` with
+/// `PicoCamera` for each additional camera.
 #[derive(Component)]
 pub struct Pico2dCamera;
 
+/// A resource that holds a `Pico` and is rendered by `render::<Self>` to its own camera.
+/// Implemented for the default `Pico` resource and for `PicoInstance`.
+pub trait PicoResource: Resource {
+    /// Marker on the camera this instance is rendered with
+    type Camera: Component;
+    fn pico_mut(&mut self) -> &mut Pico;
+}
+
+impl PicoResource for Pico {
+    type Camera = Pico2dCamera;
+    fn pico_mut(&mut self) -> &mut Pico {
+        self
+    }
+}
+
+/// An additional, independent Pico with its own state and camera, for example a debug UI on a separate
+/// camera from the game UI. `M` is a marker type to tell instances apart. Derefs to `Pico`, so widgets
+/// can be used with `&mut instance`. See `PicoInstancePlugin`.
+/// Items are spawned with the `RenderLayers` of the instance's camera, give each camera its own layer
+/// so it only draws its own instance. Interaction uses the window the camera renders to.
+#[derive(Resource, Deref, DerefMut)]
+pub struct PicoInstance<M: Send + Sync + 'static> {
+    #[deref]
+    pub pico: Pico,
+    _marker: PhantomData<M>,
+}
+
+impl<M: Send + Sync + 'static> Default for PicoInstance<M> {
+    fn default() -> Self {
+        Self {
+            pico: Pico::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M: Send + Sync + 'static> PicoResource for PicoInstance<M> {
+    type Camera = PicoCamera<M>;
+    fn pico_mut(&mut self) -> &mut Pico {
+        &mut self.pico
+    }
+}
+
+/// Camera marker for `PicoInstance<M>`
+#[derive(Component)]
+pub struct PicoCamera<M: Send + Sync + 'static>(PhantomData<M>);
+
+impl<M: Send + Sync + 'static> Default for PicoCamera<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
 #[derive(Component, Clone, Debug)]
 pub struct MaterialHandleEntity<M: Material2d>(pub Handle<M>);
 
-#[derive(Clone, Debug)]
+/// Handles, `material`, and `blend_state` are skipped when serialized, fields missing when
+/// deserializing use their default value.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ItemStyle {
     // 50% will result in a circle
     pub corner_radius: Val,
@@ -40,23 +113,93 @@ pub struct ItemStyle {
     pub border_width: Val,
     pub border_color: Color,
     pub border_softness: Val,
+    pub border_align: BorderAlign,
+    pub border_style: BorderStyle,
+    /// Length of each dash and the gap between dashes (or dots) for `BorderStyle::Dashed` and `Dotted`
+    pub border_dash: (Val, Val),
     pub font_size: Val,
     // If no font is specified, the default bevy font (a minimal subset of FiraMono) will be used.
+    #[serde(skip)]
     pub font: Handle<Font>,
+    /// If set, emoji in the text use this font and are not tinted by `text_color`.
+    /// Note that bevy renders glyph outlines, so color bitmap emoji fonts may render monochrome.
+    #[serde(skip)]
+    pub emoji_font: Option<Handle<Font>>,
     pub text_color: Color,
+    /// Color and width of an outline around the text, for text over busy backgrounds. Drawn as copies
+    /// of the text offset in 8 directions behind it, so wide outlines look jagged.
+    pub text_outline: Option<(Color, Val)>,
     pub background_color: Color,
     /// The gradient is added to the `background_color`, use Color::None on one or the other if color mixing is not desired.
     pub background_gradient: (Color, Color),
+    /// Up to `MAX_GRADIENT_STOPS` colors at positions 0.0..1.0 along the gradient, in place of
+    /// `background_gradient` when not empty. Like it, the colors are added to the `background_color`.
+    pub gradient_stops: Vec<(f32, Color)>,
+    pub gradient_kind: GradientKind,
     pub background_uv_transform: Transform,
     /// An additional transform applied only to rendering, does not affect children etc...
     pub render_transform: Transform,
     pub edge_softness: Val,
+    /// Soft shadow drawn behind the item's rect, not drawn for items with a custom material.
+    pub shadow: Option<Shadow>,
+    /// Shadow inside the item's rect, along its inner edge. The offset moves the lit area, so a positive
+    /// offset darkens the top and left edges like a recessed panel. Spread grows the shadow inward.
+    pub inner_shadow: Option<Shadow>,
+    /// Blurs `Pico::backdrop` behind the item's rect by this many pixels, for frosted glass panels.
+    /// The background color is drawn over the blur, so it should be translucent. Has no effect
+    /// unless `Pico::backdrop` is set.
+    pub backdrop_blur: Option<f32>,
+    #[serde(with = "AnchorDef")]
     pub anchor_text: Anchor,
+    /// If set, the baseline of the first line of text is placed this far below the top of the item,
+    /// replacing the vertical part of `anchor_text`. Percent is relative to the item height.
+    /// Give items in a row the same baseline to line up text of different font sizes,
+    /// like a value with a smaller unit suffix.
+    pub text_baseline: Option<Val>,
     pub justify: JustifyText,
+    /// Rounds the font size to whole physical pixels and snaps the text to the pixel grid.
+    /// Makes small text sharper, larger text looks the same. Only applies when the item is rendered,
+    /// moving a crisp item without re-rendering it can put the text back between pixels.
+    pub crisp_text: bool,
+    /// Hard aliased edges and borders with no softness, corner radii and border width are snapped
+    /// to whole physical pixels. For pixel art UIs. See also `Pico::pixel_perfect`.
+    pub pixel_perfect: bool,
+    /// Insets the region children (and stacks started on this item) are laid out within.
+    /// Left, Top, Right, Bottom. Percent is relative to the item size.
+    pub padding: (Val, Val, Val, Val),
+    /// Clip the item to its parent's bbox (and the parent's clip), like `Pico::clip()` for a single item.
+    /// Useful for rows in a scroll area whose content overflows. Text is hidden unless fully inside.
+    pub clip_to_parent: bool,
+    #[serde(skip)]
     pub material: Option<Entity>,
     /// For image to be fully opaque with the correct colors, the background needs to be white.
+    #[serde(skip)]
     pub image: Option<Handle<Image>>,
+    /// Repeats the image this many times across the item (horizontally, vertically).
+    /// Filtering doesn't wrap across tile edges unless the image's sampler uses `ImageAddressMode::Repeat`.
+    /// Takes precedence over `nine_patch`.
+    pub image_tiling: Option<Vec2>,
+    /// None uses `Pico::default_blend_state`. Use `BlendState::REPLACE` for no blending.
+    #[serde(skip)]
     pub blend_state: Option<BlendState>,
+    /// Replaces the unit rect mesh. Positions should be within -0.5..0.5, the mesh is scaled to the item size.
+    /// Corner radius, border, and edge softness are computed from the mesh uvs as if it were a rect,
+    /// so they are usually only useful with rect meshes. Hit-testing still uses the item's bbox.
+    #[serde(skip)]
+    pub mesh: Option<Handle<Mesh>>,
+    /// Expands the interactive area beyond the rendered rect on all sides, for larger touch targets.
+    /// Percent is relative to the item size.
+    pub hit_padding: Val,
+    /// Items with a higher priority are drawn over items with a lower priority, regardless of depth.
+    /// Only affects draw order, hovering/clicking still uses depth. Useful to composite a translucent
+    /// highlight over a sibling while it stays behind that sibling for interaction.
+    /// Each step offsets the drawn z by `RENDER_PRIORITY_STEP`.
+    pub render_priority: i8,
+    /// Shown next to the cursor after the item is hovered for `Pico::tooltip_delay`.
+    /// Only interactable items are hovered.
+    pub tooltip: Option<String>,
+    /// 0.0..1.0, multiplies the alpha of the item and all of its descendants
+    pub opacity: f32,
 }
 
 impl Default for ItemStyle {
@@ -73,23 +216,210 @@ impl Default for ItemStyle {
             border_width: Val::default(),
             border_color: Color::BLACK,
             border_softness: Val::Px(0.5),
+            border_align: BorderAlign::Inside,
+            border_style: BorderStyle::Solid,
+            border_dash: (Val::Px(6.0), Val::Px(4.0)),
             font_size: Val::Vh(2.0),
             font: Default::default(),
+            emoji_font: None,
             text_color: Color::WHITE,
+            text_outline: None,
             background_color: Color::NONE,
             background_gradient: (Color::NONE, Color::NONE),
+            gradient_stops: Vec::new(),
+            gradient_kind: GradientKind::Linear,
             edge_softness: Val::Px(1.0),
+            shadow: None,
+            inner_shadow: None,
+            backdrop_blur: None,
             background_uv_transform: Transform::default(),
             render_transform: Transform::default(),
             justify: JustifyText::Center,
+            crisp_text: false,
+            pixel_perfect: false,
+            padding: (
+                Val::default(),
+                Val::default(),
+                Val::default(),
+                Val::default(),
+            ),
+            clip_to_parent: false,
             anchor_text: Anchor::Center,
+            text_baseline: None,
             material: None,
             image: None,
-            blend_state: Some(BlendState::ALPHA_BLENDING),
+            image_tiling: None,
+            blend_state: None,
+            mesh: None,
+            hit_padding: Val::default(),
+            render_priority: 0,
+            tooltip: None,
+            opacity: 1.0,
         }
     }
 }
 
+macro_rules! item_style_overrides {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        /// The `ItemStyle` fields of a themed item that differ from the theme, only fields that are
+        /// `Some` are used. See `Pico::add_themed()`.
+        #[derive(Clone, Debug, Default)]
+        pub struct ItemStyleOverrides {
+            $(pub $field: Option<$ty>,)*
+        }
+
+        impl ItemStyleOverrides {
+            /// `theme` with the fields that are `Some` replaced
+            pub fn or_theme(self, theme: &ItemStyle) -> ItemStyle {
+                let mut style = theme.clone();
+                $(
+                    if let Some(value) = self.$field {
+                        style.$field = value;
+                    }
+                )*
+                style
+            }
+        }
+    };
+}
+
+item_style_overrides!(
+    corner_radius: Val,
+    multi_corner_radius: (Val, Val, Val, Val),
+    nine_patch: Option<(u32, u32, u32, u32)>,
+    border_width: Val,
+    border_color: Color,
+    border_softness: Val,
+    border_align: BorderAlign,
+    border_style: BorderStyle,
+    border_dash: (Val, Val),
+    font_size: Val,
+    font: Handle<Font>,
+    emoji_font: Option<Handle<Font>>,
+    text_color: Color,
+    text_outline: Option<(Color, Val)>,
+    background_color: Color,
+    background_gradient: (Color, Color),
+    gradient_stops: Vec<(f32, Color)>,
+    gradient_kind: GradientKind,
+    background_uv_transform: Transform,
+    render_transform: Transform,
+    edge_softness: Val,
+    shadow: Option<Shadow>,
+    inner_shadow: Option<Shadow>,
+    backdrop_blur: Option<f32>,
+    anchor_text: Anchor,
+    text_baseline: Option<Val>,
+    justify: JustifyText,
+    crisp_text: bool,
+    pixel_perfect: bool,
+    padding: (Val, Val, Val, Val),
+    clip_to_parent: bool,
+    material: Option<Entity>,
+    image: Option<Handle<Image>>,
+    image_tiling: Option<Vec2>,
+    blend_state: Option<BlendState>,
+    mesh: Option<Handle<Mesh>>,
+    hit_padding: Val,
+    render_priority: i8,
+    tooltip: Option<String>,
+    opacity: f32,
+);
+
+/// App wide style defaults, see `Pico::add_themed()`. Insert it as a resource to restyle every
+/// `Pico`, it is copied to `Pico::theme` when it changes.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct PicoTheme {
+    /// Style of themed items, with their `ItemStyleOverrides` applied
+    pub style: ItemStyle,
+}
+
+/// See `ItemStyle::shadow` and `ItemStyle::inner_shadow`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Shadow {
+    pub color: Color,
+    /// Logical pixels, +y is down
+    pub offset: Vec2,
+    /// Width of the soft edge, centered on the edge of the shadow's rect
+    pub blur: Val,
+    /// Grows (or shrinks, if negative) the shadow's rect on each side
+    pub spread: Val,
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Shadow {
+            color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+            offset: vec2(0.0, 4.0),
+            blur: Val::Px(12.0),
+            spread: Val::Px(0.0),
+        }
+    }
+}
+
+/// How `ItemStyle::background_gradient` is mapped over the item, both are affected by
+/// `ItemStyle::background_uv_transform`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GradientKind {
+    /// First color at the top, second at the bottom
+    #[default]
+    Linear,
+    /// First color at the center, second at the middle of each edge and out to the corners
+    Radial,
+}
+
+/// Easing applied by `Pico::animate()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    Smoothstep,
+}
+
+impl Easing {
+    /// `t` is 0.0..1.0
+    pub fn apply(&self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::Smoothstep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Seconds the state of `Pico::animate()` is kept after it stops being called for an id
+pub const ANIMATION_STATE_LIFE: f32 = 1.0;
+
+/// Stored in `StateItem::storage` by `Pico::animate()`
+struct Animation {
+    from: f32,
+    to: f32,
+    /// Progress from `from` to `to`, before easing
+    t: f32,
+}
+
+/// See `ItemStyle::border_dash`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BorderStyle {
+    #[default]
+    Solid,
+    Dashed,
+    /// Round dots as wide as the border
+    Dotted,
+}
+
+/// Where the border is placed relative to the edge of the item
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BorderAlign {
+    /// The border is inside the item, the visual size matches the item's rect
+    #[default]
+    Inside,
+    /// Half of the border extends outside the item
+    Center,
+    /// The border is outside the item, the visual size is larger than the item's rect
+    Outside,
+}
+
 #[derive(SystemParam)]
 pub struct PicoMaterials<'w, 's, M: Material2d> {
     q: Query<'w, 's, (Entity, &'static MaterialHandleEntity<M>)>,
@@ -128,12 +458,38 @@ impl Hash for ItemStyle {
         hash_val(&self.border_width, state);
         hash_color(&self.border_color, state);
         hash_val(&self.border_softness, state);
+        self.border_align.hash(state);
+        self.border_style.hash(state);
+        hash_val(&self.border_dash.0, state);
+        hash_val(&self.border_dash.1, state);
         hash_val(&self.font_size, state);
         self.font.hash(state);
+        self.emoji_font.hash(state);
         hash_color(&self.text_color, state);
+        if let Some((color, width)) = &self.text_outline {
+            hash_color(color, state);
+            hash_val(width, state);
+        }
         hash_color(&self.background_color, state);
         hash_color(&self.background_gradient.0, state);
         hash_color(&self.background_gradient.1, state);
+        for (position, color) in &self.gradient_stops {
+            position.to_bits().hash(state);
+            hash_color(color, state);
+        }
+        self.gradient_kind.hash(state);
+        for shadow in [&self.shadow, &self.inner_shadow] {
+            shadow.is_some().hash(state);
+            if let Some(shadow) = shadow {
+                hash_color(&shadow.color, state);
+                hash_vec2(&shadow.offset, state);
+                hash_val(&shadow.blur, state);
+                hash_val(&shadow.spread, state);
+            }
+        }
+        if let Some(blur) = self.backdrop_blur {
+            blur.to_bits().hash(state);
+        }
         if self.background_uv_transform != Transform::default() {
             let mat = self.background_uv_transform.compute_matrix();
             hash_vec4(&mat.x_axis, state);
@@ -149,21 +505,38 @@ impl Hash for ItemStyle {
             hash_vec4(&mat.w_axis, state);
         }
         hash_val(&self.edge_softness, state);
+        self.opacity.to_bits().hash(state);
         self.justify.hash(state);
+        self.crisp_text.hash(state);
+        self.pixel_perfect.hash(state);
+        self.clip_to_parent.hash(state);
         hash_anchor(&self.anchor_text, state);
+        if let Some(baseline) = &self.text_baseline {
+            hash_val(baseline, state);
+        }
         if let Some(entity) = self.material {
             entity.hash(state);
         }
         if let Some(image) = &self.image {
             image.id().dyn_hash(state);
         }
+        if let Some(tiling) = &self.image_tiling {
+            hash_vec2(tiling, state);
+        }
         self.blend_state.hash(state);
+        if let Some(mesh) = &self.mesh {
+            mesh.id().dyn_hash(state);
+        }
+        hash_val(&self.hit_padding, state);
+        self.render_priority.hash(state);
     }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct ProcessedPicoItem {
     pub text: String,
+    /// See `PicoItem::sections`
+    pub sections: Vec<(String, Color)>,
     pub style: ItemStyle,
     /// uv position within window
     uv_position: Vec2,
@@ -171,11 +544,17 @@ pub struct ProcessedPicoItem {
     uv_size: Vec2,
     /// 3d world space position.
     pub position_3d: Option<Vec3>,
+    /// See `PicoItem::scale_with_distance`
+    pub scale_with_distance: Option<DistanceScale>,
+    /// See `PicoItem::occluded_opacity`
+    pub occluded_opacity: Option<f32>,
     /// z position for 2d 1.0 is closer to camera 0.0 is further
     /// None for auto (calculated by order)
     depth: f32,
     /// Max z position of immediate children, used for auto z
     child_max_depth: f32,
+    /// `style.font_size` before scope scaling, inherited by children so scales don't compound
+    unscaled_font_size: Val,
     /// If life is 0.0, it will only live one frame (default), if life is f32::INFINITY it will live forever.
     life: f32,
     /// If the id changes, the item is re-rendered
@@ -188,9 +567,34 @@ pub struct ProcessedPicoItem {
     // Coordinates are uv space 0..1 over the whole window
     bbox: Vec4,
     anchor: Anchor,
+    /// bbox and color of each instance, see `Pico::add_instanced()`
+    instances: Vec<(Vec4, Color)>,
+    /// Convex polygon points in uv space of the window, see `Pico::add_polygon()`
+    polygon: Vec<Vec2>,
+    /// Coordinates are uv space 0..1 over the whole window, see `Pico::clip()`
+    clip: Option<Vec4>,
+    /// If false the item is never hovered/clicked and interaction passes through to items behind it.
+    pub interactable: bool,
+    /// See `PicoItem::focusable`
+    pub focusable: bool,
+    /// See `PicoItem::role`
+    pub role: Option<Role>,
+    /// `ItemStyle::opacity` multiplied by the opacity of the parent, computed by the renderer
+    opacity: f32,
 }
 
 impl ProcessedPicoItem {
+    /// Move the item and its instances/polygon, used when a stack is justified.
+    fn translate(&mut self, offset: Vec2) {
+        self.uv_position += offset;
+        self.bbox += offset.extend(offset.x).extend(offset.y);
+        for (bbox, _) in &mut self.instances {
+            *bbox += offset.extend(offset.x).extend(offset.y);
+        }
+        for point in &mut self.polygon {
+            *point += offset;
+        }
+    }
     pub fn get_uv_position(&self) -> Vec2 {
         self.uv_position
     }
@@ -215,6 +619,47 @@ impl ProcessedPicoItem {
     pub fn get_anchor(&self) -> Anchor {
         self.anchor
     }
+    pub fn get_instances(&self) -> &[(Vec4, Color)] {
+        &self.instances
+    }
+    pub fn get_polygon(&self) -> &[Vec2] {
+        &self.polygon
+    }
+    pub fn get_clip(&self) -> Option<Vec4> {
+        self.clip
+    }
+    /// Including the opacity of its ancestors, see `ItemStyle::opacity`
+    pub fn get_opacity(&self) -> f32 {
+        self.opacity
+    }
+    /// `text`, or the `sections` joined together
+    pub fn get_text(&self) -> String {
+        if self.sections.is_empty() {
+            self.text.clone()
+        } else {
+            self.sections
+                .iter()
+                .map(|(text, _)| text.as_str())
+                .collect()
+        }
+    }
+    /// If there is any text to render, in `text` or `sections`
+    pub fn has_text(&self) -> bool {
+        if self.sections.is_empty() {
+            !self.text.is_empty()
+        } else {
+            self.sections.iter().any(|(text, _)| !text.is_empty())
+        }
+    }
+    /// If the uv point is inside the clip rect, or there is no clip rect
+    pub fn in_clip(&self, uv: Vec2) -> bool {
+        self.clip
+            .map_or(true, |c| uv.cmpge(c.xy()).all() && uv.cmple(c.zw()).all())
+    }
+    /// If the bbox is fully inside the clip rect, or there is no clip rect
+    pub fn bbox_in_clip(&self) -> bool {
+        self.in_clip(self.bbox.xy()) && self.in_clip(self.bbox.zw())
+    }
     pub fn generate_id(&mut self) -> u64 {
         self.id = None;
         let state = &mut DefaultHasher::new();
@@ -222,21 +667,44 @@ impl ProcessedPicoItem {
         hash_vec4(&self.bbox, state);
         self.depth.to_bits().hash(state);
         self.text.hash(state);
+        for (text, color) in &self.sections {
+            text.hash(state);
+            hash_color(color, state);
+        }
         self.life.to_bits().hash(state);
         self.style.hash(state);
+        self.opacity.to_bits().hash(state);
+        self.interactable.hash(state);
+        for (bbox, color) in &self.instances {
+            hash_vec4(bbox, state);
+            hash_color(color, state);
+        }
+        for point in &self.polygon {
+            hash_vec2(point, state);
+        }
+        if let Some(clip) = &self.clip {
+            hash_vec4(clip, state);
+        }
         state.finish()
     }
 }
 
-#[derive(Clone, Debug)]
+/// `parent` and `role` are skipped when serialized, see `LayoutNode` for hierarchies.
+/// Fields missing when deserializing use their default value.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PicoItem {
     pub text: String,
+    /// Text runs with their own colors, used instead of `text` if not empty.
+    pub sections: Vec<(String, Color)>,
     pub x: Val,
     pub y: Val,
     pub width: Val,
     pub height: Val,
     pub style: ItemStyle,
+    #[serde(with = "AnchorDef")]
     pub anchor: Anchor,
+    #[serde(with = "AnchorDef")]
     pub anchor_parent: Anchor,
     /// uv position within window, is combined with x, y at pico.add().
     pub uv_position: Vec2,
@@ -244,6 +712,13 @@ pub struct PicoItem {
     pub uv_size: Vec2,
     /// 3d world space position.
     pub position_3d: Option<Vec3>,
+    /// Shrinks items with a `position_3d` as they get further from the camera.
+    /// None keeps a constant size on screen.
+    pub scale_with_distance: Option<DistanceScale>,
+    /// Multiplies the opacity of an item with a `position_3d` while that position is hidden from the
+    /// camera by one of the boxes added with `Pico::add_occluder()`. 0.0 hides the item and makes it
+    /// non interactable. None draws the item over the scene regardless.
+    pub occluded_opacity: Option<f32>,
     /// z position for 2d 1.0 is closer to camera 0.0 is further
     /// None for auto (calculated by order)
     pub depth: Option<f32>,
@@ -255,7 +730,17 @@ pub struct PicoItem {
     /// Impacted by position, size, anchor (after transform from parent is applied, if any)
     pub spatial_id: Option<u64>,
     /// If set, coordinates for position/size will be relative to parent.
+    #[serde(skip)]
     pub parent: Option<ItemIndex>,
+    /// If false the item is never hovered/clicked and interaction passes through to items behind it.
+    pub interactable: bool,
+    /// If the item can be focused by clicking it or with Tab, see `Pico::request_focus()`.
+    /// Set by the input widgets.
+    pub focusable: bool,
+    /// Exposes the item to screen readers through AccessKit, with its text as the label.
+    /// None uses `Role::StaticText` for items with text, other items are left out.
+    #[serde(skip)]
+    pub role: Option<Role>,
 }
 
 impl Default for PicoItem {
@@ -270,17 +755,102 @@ impl Default for PicoItem {
             anchor_parent: Anchor::TopLeft,
             uv_position: Vec2::ZERO,
             position_3d: None,
+            scale_with_distance: None,
+            occluded_opacity: None,
             depth: None,
             uv_size: Vec2::ZERO,
             text: String::new(),
+            sections: Vec::new(),
             life: 0.0,
             id: None,
             spatial_id: None,
             parent: None,
+            interactable: true,
+            focusable: false,
+            role: None,
+        }
+    }
+}
+
+/// See `PicoItem::scale_with_distance`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DistanceScale {
+    /// World units from the camera where the item is drawn at its own size
+    pub reference_distance: f32,
+    /// Limits of the scale, so far items stay readable and near items don't fill the screen
+    pub min_scale: f32,
+    pub max_scale: f32,
+}
+
+impl Default for DistanceScale {
+    fn default() -> Self {
+        DistanceScale {
+            reference_distance: 5.0,
+            min_scale: 0.25,
+            max_scale: 2.0,
         }
     }
 }
 
+impl DistanceScale {
+    pub fn scale(&self, distance: f32) -> f32 {
+        (self.reference_distance / distance.max(f32::EPSILON)).clamp(self.min_scale, self.max_scale)
+    }
+}
+
+/// Box in the scene that hides 3d items behind it, see `Pico::add_occluder()`
+#[derive(Clone, Copy, Debug)]
+pub struct Occluder {
+    /// Local space bounds of the box
+    pub min: Vec3,
+    pub max: Vec3,
+    /// Local to world space
+    pub transform: Affine3A,
+}
+
+impl Occluder {
+    /// If the segment from `start` to `end` passes through the box. Points just inside the box at the
+    /// end of the segment are not blocked, so labels can sit on the surface of the box.
+    pub fn blocks(&self, start: Vec3, end: Vec3) -> bool {
+        let inverse = self.transform.inverse();
+        let start = inverse.transform_point3(start);
+        let direction = inverse.transform_point3(end) - start;
+        let t0 = (self.min - start) / direction;
+        let t1 = (self.max - start) / direction;
+        let enter = t0.min(t1).max_element();
+        let exit = t0.max(t1).min_element();
+        enter <= exit && exit > 0.0 && enter < 0.999
+    }
+}
+
+/// Mirrors `Anchor` for serde, which bevy doesn't implement for it
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Anchor")]
+enum AnchorDef {
+    Center,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    CenterLeft,
+    CenterRight,
+    TopLeft,
+    TopCenter,
+    TopRight,
+    Custom(Vec2),
+}
+
+/// An item and its children, for describing layouts in data (RON etc...). See `Pico::add_tree()`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutNode {
+    /// `Pico::add_tree()` returns the index of the item under this name
+    pub name: Option<String>,
+    pub item: PicoItem,
+    /// Added with this node's item as their parent
+    pub children: Vec<LayoutNode>,
+}
+
 impl PicoItem {
     pub fn new2d(position: Vec2, text: &str) -> PicoItem {
         PicoItem {
@@ -300,6 +870,65 @@ impl PicoItem {
         self.life = f32::INFINITY;
         self
     }
+    /// Same as `PicoItem::default()`, for chaining the setters below.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn pos(mut self, x: Val, y: Val) -> Self {
+        self.x = x;
+        self.y = y;
+        self
+    }
+    pub fn size(mut self, width: Val, height: Val) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+    pub fn anchor_parent(mut self, anchor_parent: Anchor) -> Self {
+        self.anchor_parent = anchor_parent;
+        self
+    }
+    pub fn text(mut self, text: &str) -> Self {
+        self.text = text.to_string();
+        self
+    }
+    pub fn parent(mut self, parent: ItemIndex) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+    pub fn depth(mut self, depth: f32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+    pub fn style(mut self, style: ItemStyle) -> Self {
+        self.style = style;
+        self
+    }
+    pub fn bg(mut self, color: Color) -> Self {
+        self.style.background_color = color;
+        self
+    }
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.style.text_color = color;
+        self
+    }
+    pub fn font_size(mut self, font_size: Val) -> Self {
+        self.style.font_size = font_size;
+        self
+    }
+    pub fn corner_radius(mut self, corner_radius: Val) -> Self {
+        self.style.corner_radius = corner_radius;
+        self
+    }
+    pub fn border(mut self, width: Val, color: Color) -> Self {
+        self.style.border_width = width;
+        self.style.border_color = color;
+        self
+    }
     pub fn generate_spatial_id(
         uv_position: &Vec2,
         uv_size: &Vec2,
@@ -331,13 +960,34 @@ pub struct StateItem {
     pub interactable: bool,
     pub selected: bool,
     pub drag: Option<Drag>,
+    /// Applied to `drag` as the cursor moves, see `Pico::constrain_drag()`
+    pub drag_constraint: DragConstraint,
     pub id: u64,
     pub input: Option<ButtonInput<MouseButton>>,
+    /// Elapsed seconds (`Time::elapsed_seconds()`) when the item was last pressed with the left button.
+    /// Cleared when a press counts as a double click, so a triple click is one double click.
+    pub last_click_time: Option<f32>,
+    /// True the frame the second press of a double click happened, see `Pico::double_clicked()`
+    pub double_clicked: bool,
+    /// Seconds the item has been continuously hovered
+    pub hover_time: f32,
     // Coordinates are uv space 0..1 over the whole window
     pub bbox: Vec4,
+    /// Window uv bounds of each of the item's `sections` from the last text layout, zero for spans
+    /// without glyphs. Only updated while `track_spans` is set, see `Pico::text_links()`.
+    pub span_bboxes: Vec<Vec4>,
+    pub track_spans: bool,
     pub storage: Option<Box<dyn std::any::Any + Send + Sync>>,
 }
 
+/// Scales `v` unless it is relative to the item's size, which is already scaled
+fn scale_absolute_val(v: Val, scale: f32) -> Val {
+    match v {
+        Val::Auto | Val::Percent(_) => v,
+        _ => v * scale,
+    }
+}
+
 pub fn lerp2(start: Vec2, end: Vec2, t: Vec2) -> Vec2 {
     (1.0 - t) * start + t * end
 }
@@ -346,7 +996,16 @@ pub fn lerp(start: f32, end: f32, t: f32) -> f32 {
     (1.0 - t) * start + t * end
 }
 
-#[derive(Clone, Copy, Default)]
+/// Per instance data for `Pico::add_instanced()`
+#[derive(Clone, Copy, Debug)]
+pub struct InstanceData {
+    /// Offset from the template's position.
+    /// Unit is u, v within the template's parent.
+    pub offset: Vec2,
+    pub color: Color,
+}
+
+#[derive(Clone, Default)]
 pub struct Stack {
     // Unit for end and margin is u or v within parent
     pub end: f32,
@@ -355,6 +1014,43 @@ pub struct Stack {
     pub reverse: bool,
     pub bypass: bool,
     pub parent: Option<ItemIndex>,
+    /// See `Pico::align_items()`
+    pub cross_align: Option<CrossAlign>,
+    /// See `Pico::justify_items()`
+    pub justify: Option<Justify>,
+    /// Index and scope transform scale of each stacked item, only collected when justifying
+    /// or if the stack has a flexible spacer.
+    pub items: Vec<(usize, f32)>,
+    /// Item indices of flexible spacers, see `Pico::flexible_spacer()`
+    pub flexible_spacers: Vec<usize>,
+    /// Height of each row if the stack wraps, see `Pico::wrap_hstack()`
+    pub line_height: Option<f32>,
+    /// Where each row starts, the stack's start
+    pub line_start: f32,
+    /// Cross axis offset of the current row
+    pub line_offset: f32,
+}
+
+/// Distribution of stacked items along the stack direction, see `Pico::justify_items()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Justify {
+    Start,
+    Center,
+    End,
+    /// First and last items at the edges, remaining space split evenly between items.
+    SpaceBetween,
+    /// Remaining space split evenly around each item, so edge gaps are half the inner gaps.
+    SpaceAround,
+}
+
+/// Alignment of stacked items on the axis perpendicular to the stack, see `Pico::align_items()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrossAlign {
+    Start,
+    Center,
+    End,
+    /// Size items to the cross extent of their parent.
+    Stretch,
 }
 
 #[derive(Resource, Default)]
@@ -362,17 +1058,212 @@ pub struct Pico {
     pub state: HashMap<u64, StateItem>,
     pub items: Vec<ProcessedPicoItem>,
     pub interacting: bool,
+    /// See `Pico::any_dragging()`
+    pub dragging: bool,
     pub stack_stack: Vec<Stack>,
     pub stack_guard: Guard,
+    /// Clip rects in uv space of the window, see `Pico::clip()`
+    pub clip_stack: Vec<Vec4>,
+    pub clip_guard: Guard,
+    /// Accumulated transforms, see `Pico::translate_scope()` and `Pico::scale_scope()`
+    pub transform_stack: Vec<ScopeTransform>,
+    pub transform_guard: Guard,
     pub window_size: Vec2,
+    /// Logical pixels of the window the camera renders to, from `Camera::viewport`.
+    /// None if the camera covers the whole window.
+    pub viewport: Option<Rect>,
     pub mouse_button_input: Option<ButtonInput<MouseButton>>,
+    pub keyboard_input: Option<ButtonInput<KeyCode>>,
+    /// Mouse wheel movement last frame in lines, +y is up. See `Pico::scroll_delta()`
+    pub scroll_delta: Vec2,
+    /// Registered this frame by `Pico::consume_scroll_delta()`, routed by the renderer
+    pub scroll_areas: Vec<ScrollArea>,
+    /// Wheel movement last frame given to each scroll area, by spatial id
+    pub routed_scroll_delta: HashMap<u64, Vec2>,
+    /// Units uv of the window
+    pub cursor_uv_position: Option<Vec2>,
+    /// Window scale factor, physical pixels per logical pixel
+    pub scale_factor: f32,
     pub internal_auto_depth: f32,
+    /// Seconds elapsed last frame, used for animating widgets.
+    pub delta_seconds: f32,
+    /// Items that are re-added every frame until released. See `Pico::retain()`.
+    pub retained: BTreeMap<u64, (PicoItem, ItemIndex)>,
+    pub next_retained_id: u64,
+    /// If set, changed or new items are only re-rendered at this interval in seconds.
+    /// Input and interaction are still processed every frame. Animated items will update at this rate.
+    /// Items that changed or moved keep showing their last state until the next rebuild.
+    pub update_interval: Option<f32>,
+    pub time_since_update: f32,
+    /// Spatial id of the focused item, see `Pico::request_focus()`.
+    pub focused: Option<u64>,
+    /// True after keyboard input, false after the mouse is pressed.
+    /// The focus outline is only drawn while this is true.
+    pub focus_visible: bool,
+    pub focus_outline: FocusOutline,
+    /// Named item templates, see `Pico::register_preset()`
+    pub presets: HashMap<String, PicoItem>,
+    /// Units uv of the window, see `Pico::add_blocking_region()`
+    pub blocking_regions: Vec<Rect>,
+    /// See `Pico::add_occluder()`
+    pub occluders: Vec<Occluder>,
+    /// Depth of the front most `Pico::modal_backdrop()` added this frame
+    pub modal_depth: Option<f32>,
+    /// Log a warning when text is rendered with the default font, which is a minimal subset of FiraMono
+    /// and silently drops many characters. Each distinct text is only warned about once.
+    pub warn_default_font: bool,
+    pub default_font_warned: HashSet<String>,
+    /// User preferences that outlive items, see `Pico::set_persistent()` and `Pico::export_state()`
+    pub persistent: BTreeMap<String, ron::Value>,
+    /// Blend state for items that don't set `ItemStyle::blend_state`. None uses `BlendState::ALPHA_BLENDING`.
+    /// Straight alpha blending is right for colors and typical images. Images with transparency that are
+    /// filtered or scaled can show dark or light fringes at their edges, use
+    /// `BlendState::PREMULTIPLIED_ALPHA_BLENDING` with images that have premultiplied alpha to avoid this.
+    /// Item colors are premultiplied in the shader in that mode, so solid colors look the same with either.
+    pub default_blend_state: Option<BlendState>,
+    /// Render every item as if `ItemStyle::pixel_perfect` were set.
+    pub pixel_perfect: bool,
+    /// Max seconds between two presses for them to count as a double click.
+    /// None uses `DEFAULT_DOUBLE_CLICK_TIME`.
+    pub double_click_time: Option<f32>,
+    /// Seconds an item with `ItemStyle::tooltip` needs to be hovered before the tooltip is shown.
+    /// None uses `DEFAULT_TOOLTIP_DELAY`.
+    pub tooltip_delay: Option<f32>,
+    /// Tooltip of the hovered item, set by the renderer once it has been hovered long enough
+    pub tooltip: Option<String>,
+    /// Image of what is rendered behind the UI, sampled by items with `ItemStyle::backdrop_blur`.
+    /// Render the scene to an image with a camera that has a lower `order` than the pico camera,
+    /// show it with a full window item, and set it here.
+    pub backdrop: Option<Handle<Image>>,
+    /// See `Pico::add_themed()`, replaced by the `PicoTheme` resource when it changes
+    pub theme: PicoTheme,
+    /// Children inherit `text_color`, `font` and `font_size` from their parent if they are left at their
+    /// default value, so they only need to be set on a panel. Since the parent's style is inherited
+    /// further down, the whole subtree uses them.
+    pub inherit_text_style: bool,
+    /// Loaded fonts, copied from `Assets<Font>` by the renderer. See `Pico::measure_text()`
+    pub fonts: HashMap<AssetId<Font>, FontArc>,
+}
+
+/// How far a scroll area can move in lines, see `Pico::consume_scroll_delta()`
+#[derive(Clone, Copy, Debug)]
+pub struct ScrollArea {
+    pub spatial_id: u64,
+    pub depth: f32,
+    /// Lines it can scroll toward the start (wheel +y)
+    pub room_up: f32,
+    /// Lines it can scroll toward the end (wheel -y)
+    pub room_down: f32,
+}
+
+pub const DEFAULT_DOUBLE_CLICK_TIME: f32 = 0.3;
+pub const DEFAULT_TOOLTIP_DELAY: f32 = 0.5;
+
+/// Appearance of the outline drawn around the focused item while focus is visible.
+#[derive(Clone, Debug)]
+pub struct FocusOutline {
+    pub color: Color,
+    pub width: Val,
+    /// Gap between the item and the outline
+    pub offset: Val,
+    pub corner_radius: Val,
+    /// Drawn at this depth so the outline is not occluded by other items
+    pub depth: f32,
 }
 
+impl Default for FocusOutline {
+    fn default() -> Self {
+        FocusOutline {
+            color: Color::rgb(0.3, 0.6, 1.0),
+            width: Val::Px(2.0),
+            offset: Val::Px(2.0),
+            corner_radius: Val::Px(4.0),
+            depth: 0.99,
+        }
+    }
+}
+
+/// Uniform scale followed by an offset, in uv of the window.
+/// See `Pico::translate_scope()` and `Pico::scale_scope()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScopeTransform {
+    pub scale: f32,
+    pub offset: Vec2,
+}
+
+impl ScopeTransform {
+    pub const IDENTITY: ScopeTransform = ScopeTransform {
+        scale: 1.0,
+        offset: Vec2::ZERO,
+    };
+
+    pub fn apply(&self, uv: Vec2) -> Vec2 {
+        uv * self.scale + self.offset
+    }
+
+    pub fn inverse(&self, uv: Vec2) -> Vec2 {
+        (uv - self.offset) / self.scale
+    }
+
+    pub fn inverse_bbox(&self, bbox: Vec4) -> Vec4 {
+        let min = self.inverse(bbox.xy());
+        let max = self.inverse(bbox.zw());
+        vec4(min.x, min.y, max.x, max.y)
+    }
+
+    /// Applies `inner` first, then self
+    pub fn then(&self, inner: ScopeTransform) -> ScopeTransform {
+        ScopeTransform {
+            scale: self.scale * inner.scale,
+            offset: self.apply(inner.offset),
+        }
+    }
+}
+
+/// See `Pico::group_interaction()`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GroupInteraction {
+    /// Any of the items are hovered, use this to highlight all of them together
+    pub hovered: bool,
+    pub clicked: bool,
+    pub released: bool,
+    /// The item that received the click
+    pub clicked_item: Option<ItemIndex>,
+}
+
+/// Side of an item to place a popup on, see `Pico::popup_anchored_to()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PopupSide {
+    Above,
+    Below,
+    Left,
+    Right,
+}
+
+impl PopupSide {
+    pub fn opposite(self) -> PopupSide {
+        match self {
+            PopupSide::Above => PopupSide::Below,
+            PopupSide::Below => PopupSide::Above,
+            PopupSide::Left => PopupSide::Right,
+            PopupSide::Right => PopupSide::Left,
+        }
+    }
+}
+
+/// Handle to an item added with `Pico::retain()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RetainedHandle(u64);
+
 impl Pico {
+    /// Items with `parent` added while the guard is alive are placed one after the other from the
+    /// top of the parent (the bottom if `reverse`), `margin` apart. Use `Pico::justify_items()`
+    /// to center them, pack them at the end, or space them out, and `Pico::align_items()` for
+    /// the cross axis.
     pub fn vstack(&mut self, start: Val, margin: Val, reverse: bool, parent: &ItemIndex) -> Guard {
         self.update_stack();
-        let bbox = self.get(parent).bbox;
+        let bbox = self.scope_transform().inverse_bbox(self.get(parent).bbox);
+        let bbox = self.padded_bbox(bbox, &self.get(parent).style);
         let parent_size = (bbox.zw() - bbox.xy()).abs();
         let start = self.valp_y(start, parent_size) * if reverse { -1.0 } else { 1.0 };
         let margin = self.valp_y(margin, parent_size);
@@ -383,14 +1274,26 @@ impl Pico {
             reverse,
             bypass: false,
             parent: Some(*parent),
+            cross_align: None,
+            justify: None,
+            items: Vec::new(),
+            flexible_spacers: Vec::new(),
+            line_height: None,
+            line_start: start,
+            line_offset: 0.0,
         });
         self.stack_guard.push();
         self.stack_guard.clone()
     }
 
+    /// Items with `parent` added while the guard is alive are placed one after the other from the
+    /// left of the parent (the right if `reverse`), `margin` apart. Use `Pico::justify_items()`
+    /// to center them, pack them at the end, or space them out, and `Pico::align_items()` for
+    /// the cross axis.
     pub fn hstack(&mut self, start: Val, margin: Val, reverse: bool, parent: &ItemIndex) -> Guard {
         self.update_stack();
-        let bbox = self.get(parent).bbox;
+        let bbox = self.scope_transform().inverse_bbox(self.get(parent).bbox);
+        let bbox = self.padded_bbox(bbox, &self.get(parent).style);
         let parent_size = (bbox.zw() - bbox.xy()).abs();
         let start = self.valp_x(start, parent_size) * if reverse { -1.0 } else { 1.0 };
         let margin = self.valp_x(margin, parent_size);
@@ -401,11 +1304,40 @@ impl Pico {
             reverse,
             bypass: false,
             parent: Some(*parent),
+            cross_align: None,
+            justify: None,
+            items: Vec::new(),
+            flexible_spacers: Vec::new(),
+            line_height: None,
+            line_start: start,
+            line_offset: 0.0,
         });
         self.stack_guard.push();
         self.stack_guard.clone()
     }
 
+    /// Like `Pico::hstack()`, but an item that would extend past the right of the parent is moved
+    /// to the start of a new row, `line_height` below the previous row plus `margin`.
+    /// Items taller than `line_height` overlap the next row. Not compatible with
+    /// `Pico::justify_items()` or `Pico::align_items()`.
+    pub fn wrap_hstack(
+        &mut self,
+        start: Val,
+        margin: Val,
+        line_height: Val,
+        parent: &ItemIndex,
+    ) -> Guard {
+        let guard = self.hstack(start, margin, false, parent);
+        let bbox = self.scope_transform().inverse_bbox(self.get(parent).bbox);
+        let bbox = self.padded_bbox(bbox, &self.get(parent).style);
+        let parent_size = (bbox.zw() - bbox.xy()).abs();
+        let line_height = self.valp_y(line_height, parent_size);
+        if let Some(stack) = self.stack_stack.last_mut() {
+            stack.line_height = Some(line_height);
+        }
+        guard
+    }
+
     pub fn stack_bypass(&mut self) -> Guard {
         self.update_stack();
         self.stack_stack.push(Stack {
@@ -416,13 +1348,158 @@ impl Pico {
         self.stack_guard.clone()
     }
 
+    /// Items added while the returned guard is alive are clipped to the bbox of `index`.
+    /// Nested clips use the intersection. Text is hidden if its item is not fully inside the clip.
+    /// Instanced items and polygons are not clipped.
+    pub fn clip(&mut self, index: &ItemIndex) -> Guard {
+        self.update_clip();
+        let mut bbox = self.get(index).bbox;
+        if let Some(outer) = self.clip_stack.last() {
+            bbox = vec4(
+                bbox.x.max(outer.x),
+                bbox.y.max(outer.y),
+                bbox.z.min(outer.z),
+                bbox.w.min(outer.w),
+            );
+        }
+        self.clip_stack.push(bbox);
+        self.clip_guard.push();
+        self.clip_guard.clone()
+    }
+
+    /// Offsets all items added while the guard is alive by `offset` in uv of the window.
+    /// Nested scopes accumulate. Applied after parent and stack layout, so the offset doesn't
+    /// change stack spacing, and items keep their state (hover, drag, storage) while moving.
+    pub fn translate_scope(&mut self, offset: Vec2) -> Guard {
+        self.push_transform(ScopeTransform { scale: 1.0, offset })
+    }
+
+    /// Scales all items added while the guard is alive by `factor` around `pivot` in uv of the window.
+    /// Affects layout, rendering (including font size, border width, and corner radius), and hit-testing.
+    /// Nests with other scale and translate scopes, the pivot is relative to the enclosing scopes.
+    pub fn scale_scope(&mut self, factor: f32, pivot: Vec2) -> Guard {
+        self.push_transform(ScopeTransform {
+            scale: factor,
+            offset: pivot - pivot * factor,
+        })
+    }
+
+    fn push_transform(&mut self, inner: ScopeTransform) -> Guard {
+        let outer = self.scope_transform();
+        self.transform_stack.push(outer.then(inner));
+        self.transform_guard.push();
+        self.transform_guard.clone()
+    }
+
+    /// The combined transform of the active translate and scale scopes
+    pub fn scope_transform(&mut self) -> ScopeTransform {
+        while (self.transform_guard.get() as usize) < self.transform_stack.len() {
+            self.transform_stack.pop();
+        }
+        self.transform_stack
+            .last()
+            .copied()
+            .unwrap_or(ScopeTransform::IDENTITY)
+    }
+
+    fn update_clip(&mut self) {
+        while (self.clip_guard.get() as usize) < self.clip_stack.len() {
+            self.clip_stack.pop();
+        }
+    }
+
+    /// Splits `parent` into a grid of `count` equal cells with `columns` columns and calls `f` with each
+    /// cell and its index. Cells are filled row by row from the top left, so index `i` is in column
+    /// `i % columns` and row `i / columns`. Use 1 column for a vertical list, or `count` columns for a row.
+    /// The cells and items added in `f` bypass any active stack, `f` can start its own stacks in a cell.
+    pub fn repeat(
+        &mut self,
+        count: usize,
+        columns: usize,
+        parent: &ItemIndex,
+        mut f: impl FnMut(&mut Pico, ItemIndex, usize),
+    ) {
+        let columns = columns.max(1);
+        let rows = count.div_ceil(columns).max(1);
+        let cell_size = vec2(100.0 / columns as f32, 100.0 / rows as f32);
+        let _guard = self.stack_bypass();
+        for i in 0..count {
+            let cell = self.add(PicoItem {
+                x: Val::Percent(cell_size.x * (i % columns) as f32),
+                y: Val::Percent(cell_size.y * (i / columns) as f32),
+                width: Val::Percent(cell_size.x),
+                height: Val::Percent(cell_size.y),
+                anchor: Anchor::TopLeft,
+                parent: Some(*parent),
+                ..default()
+            });
+            f(self, cell, i);
+        }
+    }
+
+    /// Adds an invisible item taking up `size` along the current stack, or nothing if there is no stack.
+    pub fn spacer(&mut self, size: Val, parent: &ItemIndex) -> Option<ItemIndex> {
+        self.update_stack();
+        let vertical = self.stack_stack.last()?.vertical;
+        let (width, height) = if vertical {
+            (Val::Px(0.0), size)
+        } else {
+            (size, Val::Px(0.0))
+        };
+        Some(self.add(PicoItem {
+            width,
+            height,
+            anchor: Anchor::TopLeft,
+            anchor_parent: Anchor::TopLeft,
+            parent: Some(*parent),
+            interactable: false,
+            ..default()
+        }))
+    }
+
+    /// A spacer taking up the space left in the current stack, so the items added after it are pushed
+    /// to the far end. Like `Pico::justify_items()` this is applied when the stack's guard is dropped.
+    /// Multiple flexible spacers in one stack share the space equally. Overrides `Pico::justify_items()`.
+    /// Adds nothing if there is no stack.
+    pub fn flexible_spacer(&mut self, parent: &ItemIndex) -> Option<ItemIndex> {
+        self.update_stack();
+        let index = self.items.len();
+        self.stack_stack.last_mut()?.flexible_spacers.push(index);
+        self.spacer(Val::Px(0.0), parent)
+    }
+
+    /// Where the next item in the current stack will be placed, as an offset from the stack's start
+    /// edge of its parent (negative from the far edge for reversed stacks). Stacked items are already
+    /// offset by this, use it to place items added under `Pico::stack_bypass()` in line with the stack.
+    /// None if there is no stack or it is bypassed.
+    pub fn stack_cursor(&mut self) -> Option<Val> {
+        self.update_stack();
+        let stack = self.stack_stack.last().filter(|stack| !stack.bypass)?;
+        let window_px = if stack.vertical {
+            self.window_size.y
+        } else {
+            self.window_size.x
+        };
+        Some(Val::Px(stack.end * window_px))
+    }
+
+    /// If the current stack is a vstack. None if there is no stack or it is bypassed.
+    pub fn stack_is_vertical(&mut self) -> Option<bool> {
+        self.update_stack();
+        self.stack_stack
+            .last()
+            .filter(|stack| !stack.bypass)
+            .map(|stack| stack.vertical)
+    }
+
     /// Get the remaining stack for the current stack inside the stack's parent.
     /// Unit is u or v within the parent.
     pub fn remaining_stack_space(&self) -> f32 {
         if let Some(stack) = self.stack_stack.last() {
             if let Some(parent_index) = stack.parent {
                 let parent = self.get(&parent_index);
-                let parent_size = (parent.bbox.zw() - parent.bbox.xy()).abs();
+                let bbox = self.padded_bbox(parent.bbox, &parent.style);
+                let parent_size = (bbox.zw() - bbox.xy()).abs();
                 return 1.0
                     + if stack.reverse { stack.end } else { -stack.end }
                         / if stack.vertical {
@@ -445,14 +1522,92 @@ impl Pico {
     }
 
     pub fn clicked(&self, index: &ItemIndex) -> bool {
+        self.clicked_with(index, MouseButton::Left)
+    }
+
+    pub fn right_clicked(&self, index: &ItemIndex) -> bool {
+        self.clicked_with(index, MouseButton::Right)
+    }
+
+    pub fn middle_clicked(&self, index: &ItemIndex) -> bool {
+        self.clicked_with(index, MouseButton::Middle)
+    }
+
+    /// True if `button` was just pressed while the item is hovered.
+    pub fn clicked_with(&self, index: &ItemIndex, button: MouseButton) -> bool {
         if let Some(state_item) = self.get_hovered(index) {
             if let Some(input) = &state_item.input {
-                return input.just_pressed(MouseButton::Left);
+                return input.just_pressed(button);
             }
         }
         false
     }
 
+    /// True if the item was pressed with the left button twice within `Pico::double_click_time`.
+    pub fn double_clicked(&self, index: &ItemIndex) -> bool {
+        if let Some(state_item) = self.get_hovered(index) {
+            return state_item.double_clicked;
+        }
+        false
+    }
+
+    /// Combines hover and click over several items so a composite widget (icon + label) acts as one.
+    /// For clicks the first interactable item in `indices` that was clicked wins.
+    pub fn group_interaction(&self, indices: &[ItemIndex]) -> GroupInteraction {
+        let mut group = GroupInteraction::default();
+        for index in indices {
+            if !self.get(index).interactable {
+                continue;
+            }
+            group.hovered |= self.hovered(index);
+            group.released |= self.released(index);
+            if group.clicked_item.is_none() && self.clicked(index) {
+                group.clicked = true;
+                group.clicked_item = Some(*index);
+            }
+        }
+        group
+    }
+
+    /// True if the item was clicked with `button` while the modifier keys in `mods` were held.
+    /// Left and right variants of a modifier are treated the same, `KeyCode::ShiftLeft` matches either shift.
+    /// If `exact` is true no other modifiers may be held, so Ctrl+Click doesn't also trigger on Ctrl+Shift+Click.
+    pub fn clicked_with_mods(
+        &self,
+        index: &ItemIndex,
+        button: MouseButton,
+        mods: &[KeyCode],
+        exact: bool,
+    ) -> bool {
+        let Some(input) = self
+            .get_hovered(index)
+            .and_then(|state| state.input.as_ref())
+        else {
+            return false;
+        };
+        if !input.just_pressed(button) {
+            return false;
+        }
+        let Some(keyboard_input) = &self.keyboard_input else {
+            return mods.is_empty();
+        };
+        let modifiers = [
+            [KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            [KeyCode::ControlLeft, KeyCode::ControlRight],
+            [KeyCode::AltLeft, KeyCode::AltRight],
+            [KeyCode::SuperLeft, KeyCode::SuperRight],
+        ];
+        modifiers.iter().all(|keys| {
+            let wanted = mods.iter().any(|key| keys.contains(key));
+            let held = keyboard_input.any_pressed(*keys);
+            if exact {
+                wanted == held
+            } else {
+                !wanted || held
+            }
+        })
+    }
+
     pub fn released(&self, index: &ItemIndex) -> bool {
         if let Some(state_item) = self.get_hovered(index) {
             if let Some(input) = &state_item.input {
@@ -462,6 +1617,130 @@ impl Pico {
         false
     }
 
+    /// True while any item is being dragged. Dragging also sets `interacting`.
+    pub fn any_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Limit dragging the item to an axis or region, so `Drag::delta()` only reports the allowed
+    /// movement. Kept in the item's state until changed, the item's spatial id needs to be consistent
+    /// across frames. Does nothing on the first frame before the item has state.
+    pub fn constrain_drag(&mut self, index: &ItemIndex, constraint: DragConstraint) {
+        if let Some(state_item) = self.get_state_mut(index) {
+            state_item.drag_constraint = constraint;
+        }
+    }
+
+    /// True while a pico text field is being edited, gameplay keybinds should be ignored while this is set.
+    /// Widgets that consume keyboard input mark their state as `selected` while they do.
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.state.values().any(|state_item| state_item.selected)
+    }
+
+    /// Mouse wheel movement last frame in lines if the item is hovered, otherwise zero. +y is up.
+    pub fn scroll_delta(&self, index: &ItemIndex) -> Vec2 {
+        if self.hovered(index) {
+            self.scroll_delta
+        } else {
+            Vec2::ZERO
+        }
+    }
+
+    /// Like `Pico::scroll_delta()` for scroll areas that can be nested. Of the hovered areas the innermost
+    /// (by depth) gets the wheel movement first, up to its `room_up`/`room_down` in lines, the rest goes to
+    /// the next one out. The room is from before the returned delta is applied. The item's spatial id
+    /// needs to be consistent across frames.
+    pub fn consume_scroll_delta(
+        &mut self,
+        index: &ItemIndex,
+        room_up: f32,
+        room_down: f32,
+    ) -> Vec2 {
+        let item = self.get(index);
+        let spatial_id = item.spatial_id;
+        let depth = item.depth;
+        let delta = self
+            .routed_scroll_delta
+            .get(&spatial_id)
+            .copied()
+            .unwrap_or_else(|| self.scroll_delta(index));
+        self.scroll_areas.push(ScrollArea {
+            spatial_id,
+            depth,
+            room_up: (room_up - delta.y).max(0.0),
+            room_down: (room_down + delta.y).max(0.0),
+        });
+        delta
+    }
+
+    /// Moves a value toward `target` at `speed` units per second, eased from where it was when the target
+    /// last changed. The value is kept in `state` by `id`, starting at `target` the first time.
+    /// `id` can be the spatial id of an item if that item doesn't use its `StateItem::storage`.
+    pub fn animate(&mut self, id: u64, target: f32, speed: f32, easing: Easing) -> f32 {
+        let delta_seconds = self.delta_seconds;
+        let state = self.state.entry(id).or_default();
+        if state.entity.is_none() {
+            // Not kept alive by an item
+            state.life = state.life.max(ANIMATION_STATE_LIFE);
+        }
+        let storage = state.storage.get_or_insert_with(|| {
+            Box::new(Animation {
+                from: target,
+                to: target,
+                t: 1.0,
+            })
+        });
+        let Some(animation) = storage.downcast_mut::<Animation>() else {
+            return target;
+        };
+        if animation.to != target {
+            animation.from = lerp(animation.from, animation.to, easing.apply(animation.t));
+            animation.to = target;
+            animation.t = 0.0;
+        }
+        let distance = (animation.to - animation.from).abs();
+        animation.t = if distance > 0.0 {
+            (animation.t + speed * delta_seconds / distance).min(1.0)
+        } else {
+            1.0
+        };
+        lerp(animation.from, animation.to, easing.apply(animation.t))
+    }
+
+    /// True if the key was pressed this frame
+    pub fn key_just_pressed(&self, key: KeyCode) -> bool {
+        self.keyboard_input
+            .as_ref()
+            .is_some_and(|input| input.just_pressed(key))
+    }
+
+    /// True the frame a keyboard shortcut is completed, when exactly the `keys` are held and the last
+    /// of them was just pressed. Left and right variants of a modifier are treated the same, so
+    /// `&[KeyCode::ControlLeft, KeyCode::KeyS]` also fires for right Ctrl+S.
+    /// Shortcuts without modifiers should usually be skipped while `wants_keyboard_input()`.
+    pub fn shortcut(&self, keys: &[KeyCode]) -> bool {
+        let (Some(keyboard_input), Some(last)) = (&self.keyboard_input, keys.last()) else {
+            return false;
+        };
+        let normalize = |key: &KeyCode| match key {
+            KeyCode::ShiftRight => KeyCode::ShiftLeft,
+            KeyCode::ControlRight => KeyCode::ControlLeft,
+            KeyCode::AltRight => KeyCode::AltLeft,
+            KeyCode::SuperRight => KeyCode::SuperLeft,
+            key => *key,
+        };
+        let last = normalize(last);
+        if !keyboard_input
+            .get_just_pressed()
+            .any(|key| normalize(key) == last)
+        {
+            return false;
+        }
+        let wanted: HashSet<KeyCode> = keys.iter().map(normalize).collect();
+        let held: HashSet<KeyCode> = keyboard_input.get_pressed().map(normalize).collect();
+        wanted == held
+    }
+
     pub fn center(&self, index: &ItemIndex) -> Vec2 {
         let bbox = self.get(index).bbox;
         (bbox.xy() + bbox.zw()) / 2.0
@@ -476,6 +1755,49 @@ impl Pico {
         self.internal_auto_depth
     }
 
+    /// For setting the theme when inserting the resource:
+    /// `app.insert_resource(Pico::default().with_theme(theme))`
+    pub fn with_theme(mut self, theme: PicoTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Adds the node's item and its children, each child parented to its node's item.
+    /// Returns the indices of the named nodes, to look up items for interaction.
+    /// Handles are not serialized, set them with `pico.get_mut()` on the returned indices.
+    pub fn add_tree(&mut self, node: &LayoutNode) -> HashMap<String, ItemIndex> {
+        let mut names = HashMap::new();
+        self.add_tree_node(node, node.item.parent, &mut names);
+        names
+    }
+
+    fn add_tree_node(
+        &mut self,
+        node: &LayoutNode,
+        parent: Option<ItemIndex>,
+        names: &mut HashMap<String, ItemIndex>,
+    ) {
+        let index = self.add(PicoItem {
+            parent,
+            ..node.item.clone()
+        });
+        if let Some(name) = &node.name {
+            names.insert(name.clone(), index);
+        }
+        for child in &node.children {
+            self.add_tree_node(child, Some(index), names);
+        }
+    }
+
+    /// Like `Pico::add()`, but the item's style is `Pico::theme` with the fields set in `style`
+    /// replaced. `item.style` is ignored.
+    pub fn add_themed(&mut self, item: PicoItem, style: ItemStyleOverrides) -> ItemIndex {
+        self.add(PicoItem {
+            style: style.or_theme(&self.theme.style),
+            ..item
+        })
+    }
+
     pub fn add(&mut self, item: PicoItem) -> ItemIndex {
         let mut item_depth = item.depth;
         let item_x = item.x;
@@ -486,6 +1808,7 @@ impl Pico {
         let item_spatial_id = item.spatial_id;
         let mut processed_item = ProcessedPicoItem {
             text: item.text,
+            sections: item.sections,
             style: item.style,
             uv_position: item.uv_position,
             uv_size: item.uv_size,
@@ -494,14 +1817,37 @@ impl Pico {
             parent: item.parent,
             anchor: item.anchor,
             position_3d: item.position_3d,
+            scale_with_distance: item.scale_with_distance,
+            occluded_opacity: item.occluded_opacity,
             child_max_depth: 0.0,
+            unscaled_font_size: default(),
             spatial_id: default(),
             depth: default(),
             bbox: default(),
+            instances: Vec::new(),
+            polygon: Vec::new(),
+            clip: None,
+            interactable: item.interactable,
+            focusable: item.focusable,
+            role: item.role,
+            opacity: 1.0,
         };
 
         if let Some(parent_index) = processed_item.parent {
             let parent = self.get(&parent_index);
+            if self.inherit_text_style {
+                let default = ItemStyle::default();
+                let style = &mut processed_item.style;
+                if style.text_color == default.text_color {
+                    style.text_color = parent.style.text_color;
+                }
+                if style.font == default.font {
+                    style.font = parent.style.font.clone();
+                }
+                if style.font_size == default.font_size {
+                    style.font_size = parent.unscaled_font_size;
+                }
+            }
             if let Some(depth) = &mut item_depth {
                 *depth += parent.depth;
                 if *depth == parent.depth {
@@ -522,10 +1868,13 @@ impl Pico {
 
         processed_item.depth = item_depth.unwrap();
 
+        // Layout happens before the scope transform, so parents are brought back into the scope's space
+        let transform = self.scope_transform();
         let parent_bbox = if let Some(parent_index) = processed_item.parent {
             let parent = self.get_mut(&parent_index);
             parent.child_max_depth = parent.child_max_depth.max(processed_item.depth);
-            self.get(&parent_index).bbox
+            let parent = self.get(&parent_index);
+            self.padded_bbox(transform.inverse_bbox(parent.bbox), &parent.style)
         } else {
             vec4(0.0, 0.0, 1.0, 1.0)
         };
@@ -561,9 +1910,34 @@ impl Pico {
         processed_item.uv_size *= (parent_bbox.zw() - parent_bbox.xy()).abs();
 
         self.update_stack();
+        let mut justified = false;
         if !self.stack_stack.is_empty() && processed_item.parent.is_some() {
             let stack = self.stack_stack.last_mut().unwrap();
+            justified =
+                !stack.bypass && (stack.justify.is_some() || !stack.flexible_spacers.is_empty());
             if !stack.bypass {
+                if let Some(align) = stack.cross_align {
+                    let axis = if stack.vertical { 0 } else { 1 };
+                    let (parent_min, parent_max) = (
+                        parent_bbox.xy()[axis].min(parent_bbox.zw()[axis]),
+                        parent_bbox.xy()[axis].max(parent_bbox.zw()[axis]),
+                    );
+                    if align == CrossAlign::Stretch {
+                        processed_item.uv_size[axis] = parent_max - parent_min;
+                    }
+                    let bbox = get_bbox(
+                        processed_item.uv_size,
+                        processed_item.uv_position,
+                        &processed_item.anchor,
+                    );
+                    let (item_min, item_max) = (bbox.xy()[axis], bbox.zw()[axis]);
+                    let offset = match align {
+                        CrossAlign::Start | CrossAlign::Stretch => parent_min - item_min,
+                        CrossAlign::Center => (parent_min + parent_max - item_min - item_max) * 0.5,
+                        CrossAlign::End => parent_max - item_max,
+                    };
+                    processed_item.uv_position[axis] += offset;
+                }
                 if stack.vertical {
                     processed_item.uv_position.y += stack.end;
                     let bbox = get_bbox(
@@ -578,11 +1952,27 @@ impl Pico {
                     }
                 } else {
                     processed_item.uv_position.x += stack.end;
-                    let bbox = get_bbox(
+                    processed_item.uv_position.y += stack.line_offset;
+                    let mut bbox = get_bbox(
                         processed_item.uv_size,
                         processed_item.uv_position,
                         &processed_item.anchor,
                     );
+                    // The first item of a row stays even if it's too wide, it wouldn't fit on the next
+                    if let Some(line_height) = stack.line_height {
+                        if bbox.z > parent_bbox.z && stack.end > stack.line_start {
+                            let row_step = line_height + stack.margin;
+                            processed_item.uv_position +=
+                                vec2(stack.line_start - stack.end, row_step);
+                            stack.line_offset += row_step;
+                            stack.end = stack.line_start;
+                            bbox = get_bbox(
+                                processed_item.uv_size,
+                                processed_item.uv_position,
+                                &processed_item.anchor,
+                            );
+                        }
+                    }
                     if stack.reverse {
                         stack.end = stack.end.min(bbox.x - parent_bbox.z) - stack.margin;
                     } else {
@@ -602,6 +1992,25 @@ impl Pico {
             &processed_item.parent,
         ));
 
+        // After the spatial id so transformed items keep their state while animating
+        processed_item.uv_position = transform.apply(processed_item.uv_position);
+        processed_item.uv_size *= transform.scale;
+        processed_item.unscaled_font_size = processed_item.style.font_size;
+        if transform.scale != 1.0 {
+            let scale = |v: Val| scale_absolute_val(v, transform.scale);
+            let style = &mut processed_item.style;
+            style.font_size = scale(style.font_size);
+            style.border_width = scale(style.border_width);
+            style.corner_radius = scale(style.corner_radius);
+            let radii = &mut style.multi_corner_radius;
+            *radii = (
+                scale(radii.0),
+                scale(radii.1),
+                scale(radii.2),
+                scale(radii.3),
+            );
+        }
+
         processed_item.bbox = if processed_item.position_3d.is_some() {
             if let Some(state_item) = self.state.get(&processed_item.spatial_id) {
                 state_item.bbox
@@ -615,7 +2024,26 @@ impl Pico {
                 &processed_item.anchor,
             )
         };
+        self.update_clip();
+        processed_item.clip = self.clip_stack.last().copied();
+        if processed_item.style.clip_to_parent {
+            if let Some(parent) = processed_item.parent {
+                let parent = self.get(&parent);
+                let clip = [parent.clip, processed_item.clip]
+                    .into_iter()
+                    .flatten()
+                    .fold(parent.bbox, |a, b| {
+                        vec4(a.x.max(b.x), a.y.max(b.y), a.z.min(b.z), a.w.min(b.w))
+                    });
+                processed_item.clip = Some(clip);
+            }
+        }
         self.items.push(processed_item);
+        if justified {
+            let index = self.items.len() - 1;
+            let stack = self.stack_stack.last_mut().unwrap();
+            stack.items.push((index, transform.scale));
+        }
         ItemIndex(self.items.len() - 1)
     }
 
@@ -625,7 +2053,8 @@ impl Pico {
     /// Respects parent and parent_anchor
     pub fn add_line(&mut self, mut item: PicoItem, end_x: Val, end_y: Val) -> ItemIndex {
         let parent_size = if let Some(parent) = item.parent {
-            let bbox = self.get(&parent).bbox;
+            let parent = self.get(&parent);
+            let bbox = self.padded_bbox(parent.bbox, &parent.style);
             (bbox.xy() - bbox.zw()).abs()
         } else {
             vec2(1.0, 1.0)
@@ -657,12 +2086,371 @@ impl Pico {
         self.add(item)
     }
 
-    fn update_stack(&mut self) {
+    /// Adds many copies of `template`, rendered together as a single batched mesh with one entity.
+    /// Each instance only has its own offset and color, style and text of the template are not
+    /// rendered. The returned item covers all the instances, use `hovered_instance()` and
+    /// `clicked_instance()` to interact with individual instances.
+    pub fn add_instanced(
+        &mut self,
+        mut template: PicoItem,
+        instances: &[InstanceData],
+    ) -> ItemIndex {
+        template.text = String::new();
+        template.sections = Vec::new();
+        let index = self.add(template);
+        let transform = self.scope_transform();
+        let parent_bbox = if let Some(parent_index) = self.get(&index).parent {
+            transform.inverse_bbox(self.get(&parent_index).bbox)
+        } else {
+            vec4(0.0, 0.0, 1.0, 1.0)
+        };
+        let parent_size = (parent_bbox.zw() - parent_bbox.xy()).abs() * transform.scale;
+        let item = self.get_mut(&index);
+        let bbox = item.bbox;
+        item.instances = instances
+            .iter()
+            .map(|instance| {
+                let offset = instance.offset * parent_size;
+                (
+                    bbox + vec4(offset.x, offset.y, offset.x, offset.y),
+                    instance.color,
+                )
+            })
+            .collect();
+        if !item.instances.is_empty() {
+            // Cover all instances so the item is hovered over any of them
+            let mut union = item.instances[0].0;
+            for (bbox, _) in &item.instances {
+                union = vec4(
+                    union.x.min(bbox.x),
+                    union.y.min(bbox.y),
+                    union.z.max(bbox.z),
+                    union.w.max(bbox.w),
+                );
+            }
+            item.bbox = union;
+            item.uv_position = union.xy();
+            item.uv_size = union.zw() - union.xy();
+            item.anchor = Anchor::TopLeft;
+        }
+        index
+    }
+
+    /// Adds a filled convex polygon. Points are uv within the parent (or window if there is no parent).
+    /// The item's bbox covers the points, hovering/clicking is tested against the polygon itself.
+    pub fn add_polygon(
+        &mut self,
+        points: &[Vec2],
+        color: Color,
+        parent: Option<ItemIndex>,
+    ) -> ItemIndex {
+        let mut min = Vec2::splat(f32::MAX);
+        let mut max = Vec2::splat(f32::MIN);
+        for point in points {
+            min = min.min(*point);
+            max = max.max(*point);
+        }
+        if points.is_empty() {
+            (min, max) = (Vec2::ZERO, Vec2::ZERO);
+        }
+        let index = {
+            let _guard = self.stack_bypass();
+            self.add(PicoItem {
+                uv_position: min,
+                uv_size: max - min,
+                style: ItemStyle {
+                    background_color: color,
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent,
+                ..default()
+            })
+        };
+        // Relative to the item's bbox so the polygon follows translate and scale scopes
+        let bbox = self.get(&index).bbox;
+        let extent = (max - min).max(Vec2::splat(f32::EPSILON));
+        self.get_mut(&index).polygon = points
+            .iter()
+            .map(|p| lerp2(bbox.xy(), bbox.zw(), (*p - min) / extent))
+            .collect();
+        index
+    }
+
+    /// The topmost instance of an item from `add_instanced()` under the cursor.
+    pub fn hovered_instance(&self, index: &ItemIndex) -> Option<usize> {
+        if !self.hovered(index) {
+            return None;
+        }
+        let cursor = self.cursor_uv_position?;
+        self.get(index)
+            .instances
+            .iter()
+            .rposition(|(bbox, _)| cursor.cmpge(bbox.xy()).all() && cursor.cmple(bbox.zw()).all())
+    }
+
+    pub fn clicked_instance(&self, index: &ItemIndex) -> Option<usize> {
+        if self.clicked(index) {
+            self.hovered_instance(index)
+        } else {
+            None
+        }
+    }
+
+    /// Adds an invisible interactable item over each of `spans`, indices into the `sections` of the
+    /// text item, so `clicked()` and `hovered()` can be used on parts of the text like links.
+    /// Span bounds come from the previous frame's text layout, so links can't be clicked on the
+    /// first frame. A span that wraps onto several lines is hit tested with the bounds of all of them.
+    pub fn text_links(&mut self, index: &ItemIndex, spans: &[usize]) -> Vec<ItemIndex> {
+        let item = self.get(index);
+        let (spatial_id, depth) = (item.get_spatial_id(), item.get_depth());
+        let span_bboxes = match self.state.get_mut(&spatial_id) {
+            Some(state_item) => {
+                state_item.track_spans = true;
+                state_item.span_bboxes.clone()
+            }
+            None => Vec::new(),
+        };
+        let _guard = self.stack_bypass();
+        spans
+            .iter()
+            .map(|span| {
+                let bbox = span_bboxes.get(*span).copied().unwrap_or(Vec4::ZERO);
+                let mut hasher = DefaultHasher::new();
+                (spatial_id, "text_link", span).hash(&mut hasher);
+                self.add(PicoItem {
+                    uv_position: bbox.xy(),
+                    uv_size: bbox.zw() - bbox.xy(),
+                    anchor: Anchor::TopLeft,
+                    // In front of the text so links are hit before it
+                    depth: Some(depth + MAJOR_DEPTH_AUTO_STEP),
+                    spatial_id: Some(hasher.finish()), // Manually set id
+                    focusable: true,
+                    role: Some(Role::Link),
+                    ..default()
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the item this frame and keeps re-adding it every following frame until
+    /// `Pico::release()` is called, without the caller needing to add it again.
+    /// Retained items are hit-tested and depth sorted like any other item.
+    /// `parent` is not supported since item indices only last one frame.
+    pub fn retain(&mut self, mut item: PicoItem) -> RetainedHandle {
+        item.parent = None;
+        let handle = RetainedHandle(self.next_retained_id);
+        self.next_retained_id += 1;
+        let index = self.add(item.clone());
+        self.retained.insert(handle.0, (item, index));
+        handle
+    }
+
+    /// Stop re-adding the retained item. It will be removed the following frame.
+    pub fn release(&mut self, handle: RetainedHandle) {
+        self.retained.remove(&handle.0);
+    }
+
+    /// Index of the retained item for this frame, for use with `hovered()`, `clicked()`, etc...
+    pub fn retained_index(&self, handle: &RetainedHandle) -> Option<ItemIndex> {
+        self.retained.get(&handle.0).map(|(_, index)| *index)
+    }
+
+    /// Modify the retained item. Changes take effect the next frame.
+    pub fn get_retained_mut(&mut self, handle: &RetainedHandle) -> Option<&mut PicoItem> {
+        self.retained.get_mut(&handle.0).map(|(item, _)| item)
+    }
+
+    /// Called by the renderer at the start of each frame.
+    pub(crate) fn add_retained(&mut self) {
+        let mut retained = std::mem::take(&mut self.retained);
+        for (item, index) in retained.values_mut() {
+            *index = self.add(item.clone());
+        }
+        self.retained = retained;
+    }
+
+    /// Focus is tracked by spatial id, so it stays on the item across frames as long as it doesn't move.
+    /// Clicking a focusable item focuses it, Tab and Shift+Tab move focus through focusable items in the
+    /// order they were added. See `PicoItem::focusable`.
+    pub fn request_focus(&mut self, index: &ItemIndex) {
+        self.focused = Some(self.get(index).spatial_id);
+    }
+
+    pub fn has_focus(&self, index: &ItemIndex) -> bool {
+        self.focused == Some(self.get(index).spatial_id)
+    }
+
+    /// Outlines the focused item using its bbox from the last render.
+    pub(crate) fn add_focus_outline(&mut self) {
+        let Some(focused) = self.focused else {
+            return;
+        };
+        let Some(state_item) = self.state.get(&focused) else {
+            self.focused = None;
+            return;
+        };
+        if !self.focus_visible {
+            return;
+        }
+        let bbox = state_item.bbox;
+        let outline = self.focus_outline.clone();
+        let grow = vec2(
+            self.val_x(outline.offset) + self.val_x(outline.width),
+            self.val_y(outline.offset) + self.val_y(outline.width),
+        );
+        self.add(PicoItem {
+            uv_position: (bbox.xy() + bbox.zw()) * 0.5,
+            uv_size: bbox.zw() - bbox.xy() + grow * 2.0,
+            depth: Some(outline.depth),
+            style: ItemStyle {
+                corner_radius: outline.corner_radius,
+                border_width: outline.width,
+                border_color: outline.color,
+                ..default()
+            },
+            interactable: false,
+            ..default()
+        });
+    }
+
+    /// Sized to fit the text, below and to the right of the cursor while there is room
+    pub(crate) fn add_tooltip(&mut self) {
+        let (Some(text), Some(cursor)) = (self.tooltip.clone(), self.cursor_uv_position) else {
+            return;
+        };
+        let style = ItemStyle::default();
+        let padding = vec2(8.0, 4.0);
+        let size_px = self.measure_text(&text, &style.font, style.font_size) + padding * 2.0;
+        let size = size_px / self.window_size.max(Vec2::ONE);
+        let offset = vec2(12.0, 16.0) / self.window_size.max(Vec2::ONE);
+        let mut position = cursor + offset;
+        if position.x + size.x > 1.0 {
+            position.x = cursor.x - size.x;
+        }
+        if position.y + size.y > 1.0 {
+            position.y = cursor.y - size.y;
+        }
+        let _guard = self.stack_bypass();
+        self.add(PicoItem {
+            text,
+            uv_position: position.max(Vec2::ZERO),
+            uv_size: size,
+            anchor: Anchor::TopLeft,
+            depth: Some(0.999),
+            style: ItemStyle {
+                background_color: Color::rgb(0.1, 0.1, 0.1),
+                border_color: Color::rgb(0.4, 0.4, 0.4),
+                border_width: Val::Px(1.0),
+                corner_radius: Val::Px(4.0),
+                ..default()
+            },
+            interactable: false,
+            ..default()
+        });
+    }
+
+    /// Align items added to the current stack on its cross axis (x for vstack, y for hstack)
+    /// within their parent, overriding their anchor placement on that axis.
+    /// `Stretch` sizes items to the parent's cross extent; items have no min/max size so
+    /// constrain them with a wrapper parent if needed.
+    pub fn align_items(&mut self, align: CrossAlign) {
+        self.update_stack();
+        if let Some(stack) = self.stack_stack.last_mut() {
+            stack.cross_align = Some(align);
+        }
+    }
+
+    /// Distribute items added to the current stack along its direction once its guard is dropped.
+    /// The stack's margin is kept between items, leftover space in the parent is distributed.
+    /// Bboxes read with `Pico::get()` before the guard is dropped are not yet justified.
+    pub fn justify_items(&mut self, justify: Justify) {
+        self.update_stack();
+        if let Some(stack) = self.stack_stack.last_mut() {
+            stack.justify = Some(justify);
+        }
+    }
+
+    pub(crate) fn update_stack(&mut self) {
         while (self.stack_guard.get() as usize) < self.stack_stack.len() {
-            self.stack_stack.pop();
+            if let Some(stack) = self.stack_stack.pop() {
+                self.justify_stack(stack);
+            }
+        }
+    }
+
+    fn justify_stack(&mut self, stack: Stack) {
+        let Some(parent) = stack.parent else {
+            return;
+        };
+        if stack.justify.is_none() && stack.flexible_spacers.is_empty() {
+            return;
+        }
+        let Some(&(first, _)) = stack.items.first() else {
+            return;
+        };
+        let axis = if stack.vertical { 1 } else { 0 };
+        let bbox = self.scope_transform().inverse_bbox(self.get(&parent).bbox);
+        let bbox = self.padded_bbox(bbox, &self.get(&parent).style);
+        let extent = (bbox.zw() - bbox.xy()).abs()[axis];
+        let used = stack.end.abs() - stack.margin;
+        let free = (extent - used).max(0.0);
+        let n = stack.items.len() as f32;
+        let direction = if stack.reverse { -1.0 } else { 1.0 };
+
+        // Items are added after their parents, so one forward pass also moves descendants.
+        let mut offsets = HashMap::new();
+        let spacers = stack.flexible_spacers.len() as f32;
+        for (k, &(index, scale)) in stack.items.iter().enumerate() {
+            let k = k as f32;
+            let offset = match stack.justify {
+                _ if spacers > 0.0 => {
+                    let before = stack
+                        .flexible_spacers
+                        .iter()
+                        .filter(|s| **s < index)
+                        .count();
+                    free * before as f32 / spacers
+                }
+                None | Some(Justify::Start) => 0.0,
+                Some(Justify::Center) => free * 0.5,
+                Some(Justify::End) => free,
+                Some(Justify::SpaceBetween) if n > 1.0 => free * k / (n - 1.0),
+                Some(Justify::SpaceBetween) => 0.0,
+                Some(Justify::SpaceAround) => free * (k + 0.5) / n,
+            };
+            let mut v = Vec2::ZERO;
+            v[axis] = offset * direction * scale;
+            offsets.insert(index, v);
+        }
+        for index in first..self.items.len() {
+            let item = &self.items[index];
+            let offset = match offsets.get(&index) {
+                Some(offset) => *offset,
+                None => match item.parent.and_then(|p| offsets.get(&p.0)) {
+                    Some(offset) => *offset,
+                    None => continue,
+                },
+            };
+            offsets.insert(index, offset);
+            if offset != Vec2::ZERO {
+                self.items[index].translate(offset);
+            }
         }
     }
 
+    /// `bbox` inset by `style.padding`, the region the item's children are laid out within
+    fn padded_bbox(&self, bbox: Vec4, style: &ItemStyle) -> Vec4 {
+        let size = (bbox.zw() - bbox.xy()).abs();
+        let (left, top, right, bottom) = style.padding;
+        bbox + vec4(
+            self.valp_x(left, size),
+            self.valp_y(top, size),
+            -self.valp_x(right, size),
+            -self.valp_y(bottom, size),
+        )
+    }
+
     // get scaled u of uv for val
     pub fn valp_x(&self, x: Val, parent_size: Vec2) -> f32 {
         match x {
@@ -713,6 +2501,34 @@ impl Pico {
         self.val_y(v) * self.window_size.y
     }
 
+    /// Size in pixels of the text as a single section, lines are split at '\n' but not wrapped.
+    /// Zero until the font is loaded and the renderer has run once.
+    pub fn measure_text(&self, text: &str, font: &Handle<Font>, font_size: Val) -> Vec2 {
+        let Some(font) = self.fonts.get(&font.id()) else {
+            return Vec2::ZERO;
+        };
+        let font = font.as_scaled(self.val_y_px(font_size));
+        let mut size = Vec2::ZERO;
+        for (i, line) in text.split('\n').enumerate() {
+            let mut width = 0.0;
+            let mut last_glyph = None;
+            for c in line.chars() {
+                let glyph = font.glyph_id(c);
+                if let Some(last_glyph) = last_glyph {
+                    width += font.kern(last_glyph, glyph);
+                }
+                width += font.h_advance(glyph);
+                last_glyph = Some(glyph);
+            }
+            size.x = size.x.max(width);
+            if i > 0 {
+                size.y += font.line_gap();
+            }
+            size.y += font.height();
+        }
+        size
+    }
+
     pub fn uv_scale_to_px(&self, uv: Vec2) -> Vec2 {
         uv * self.window_size
     }
@@ -727,13 +2543,46 @@ impl Pico {
         (uv - 0.5) * vec2(1.0, -1.0) * self.window_size
     }
 
+    /// The item's rect in logical pixels of the window, origin top left, +y down.
+    /// Same convention as `Window::cursor_position()`. Offset and scaled to the camera's viewport
+    /// if it has one.
+    pub fn screen_rect(&self, index: &ItemIndex) -> Rect {
+        let bbox = self.get(index).bbox;
+        let viewport = self
+            .viewport
+            .unwrap_or(Rect::from_corners(Vec2::ZERO, self.window_size));
+        Rect::from_corners(
+            viewport.min + bbox.xy() * viewport.size(),
+            viewport.min + bbox.zw() * viewport.size(),
+        )
+    }
+
+    /// The item's rect in physical pixels of the window, for cropping a screenshot from
+    /// `ScreenshotManager::take_screenshot()` with `crop_image()`.
+    pub fn screenshot_region(&self, index: &ItemIndex) -> URect {
+        let rect = self.screen_rect(index);
+        let physical_size = (self.window_size * self.scale_factor).round();
+        URect::from_corners(
+            (rect.min * self.scale_factor)
+                .round()
+                .clamp(Vec2::ZERO, physical_size)
+                .as_uvec2(),
+            (rect.max * self.scale_factor)
+                .round()
+                .clamp(Vec2::ZERO, physical_size)
+                .as_uvec2(),
+        )
+    }
+
+    /// None if there is no state yet or the index is out of range.
     pub fn get_state_mut(&mut self, index: &ItemIndex) -> Option<&mut StateItem> {
-        let id = self.get(index).spatial_id;
+        let id = self.try_get(index)?.spatial_id;
         self.state.get_mut(&id)
     }
 
+    /// None if there is no state yet or the index is out of range.
     pub fn get_state(&self, index: &ItemIndex) -> Option<&StateItem> {
-        self.state.get(&self.get(index).spatial_id)
+        self.state.get(&self.try_get(index)?.spatial_id)
     }
 
     pub fn get_mut(&mut self, index: &ItemIndex) -> &mut ProcessedPicoItem {
@@ -758,6 +2607,137 @@ impl Pico {
         &self.items[index.0]
     }
 
+    /// Like `Pico::get()` but returns None if the index is out of range, for indices that may be
+    /// stale, like ones kept from a previous frame.
+    pub fn try_get(&self, index: &ItemIndex) -> Option<&ProcessedPicoItem> {
+        self.items.get(index.0)
+    }
+
+    /// Like `Pico::get_mut()` but returns None if the index is out of range.
+    pub fn try_get_mut(&mut self, index: &ItemIndex) -> Option<&mut ProcessedPicoItem> {
+        self.items.get_mut(index.0)
+    }
+
+    /// Top left uv position of the window for a popup of `popup_size` (also uv of the window) placed
+    /// against `side` of the item, use it with `Anchor::TopLeft`. Above and below are aligned to the
+    /// item's left edge, left and right to its top edge. If the popup would go offscreen it flips to the
+    /// opposite side when it fits there, then it is shifted to stay on screen.
+    pub fn popup_anchored_to(&self, index: &ItemIndex, side: PopupSide, popup_size: Vec2) -> Vec2 {
+        let bbox = self.get(index).bbox;
+        let fits = |side| match side {
+            PopupSide::Above => bbox.y - popup_size.y >= 0.0,
+            PopupSide::Below => bbox.w + popup_size.y <= 1.0,
+            PopupSide::Left => bbox.x - popup_size.x >= 0.0,
+            PopupSide::Right => bbox.z + popup_size.x <= 1.0,
+        };
+        let side = if !fits(side) && fits(side.opposite()) {
+            side.opposite()
+        } else {
+            side
+        };
+        let position = match side {
+            PopupSide::Above => vec2(bbox.x, bbox.y - popup_size.y),
+            PopupSide::Below => vec2(bbox.x, bbox.w),
+            PopupSide::Left => vec2(bbox.x - popup_size.x, bbox.y),
+            PopupSide::Right => vec2(bbox.z, bbox.y),
+        };
+        position.clamp(Vec2::ZERO, (1.0 - popup_size).max(Vec2::ZERO))
+    }
+
+    /// Suppresses pico hover/click while the cursor is inside `rect`, for regions covered by
+    /// something else like another UI system or a video overlay. Units are uv of the window.
+    /// Like items, regions only last one frame and need to be added every frame.
+    pub fn add_blocking_region(&mut self, rect: Rect) {
+        self.blocking_regions.push(rect);
+    }
+
+    /// Items with `PicoItem::occluded_opacity` are faded while the box is between the camera and
+    /// their `position_3d`. Usually the `Aabb` and `GlobalTransform` of a mesh entity.
+    /// Like items, occluders only last one frame and need to be added every frame.
+    pub fn add_occluder(&mut self, aabb: &Aabb, transform: &GlobalTransform) {
+        self.occluders.push(Occluder {
+            min: aabb.min().into(),
+            max: aabb.max().into(),
+            transform: transform.affine(),
+        });
+    }
+
+    /// Dims the whole window at `depth`. Items behind it can't be hovered or clicked, and clicks on the
+    /// backdrop itself don't fall through, so only items in front of it can be interacted with. Parent a
+    /// dialog to the returned index to put it in front. Like items, it needs to be added every frame.
+    pub fn modal_backdrop(&mut self, depth: f32) -> ItemIndex {
+        self.modal_depth = Some(self.modal_depth.map_or(depth, |d| d.max(depth)));
+        let _guard = self.stack_bypass();
+        self.add(PicoItem {
+            uv_position: Vec2::ZERO,
+            uv_size: Vec2::ONE,
+            anchor: Anchor::TopLeft,
+            depth: Some(depth),
+            style: ItemStyle {
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+                ..default()
+            },
+            ..default()
+        })
+    }
+
+    /// Stores a value under a stable string key, for state that should be kept across sessions like
+    /// a collapsed section or a selected tab. Only these values are exported, not item state like hover or drag.
+    /// `collapsing()`, `tree()` nodes, `switch()`, and `ScrollAreaWidget` store their state here and
+    /// restore it when they first appear.
+    pub fn set_persistent<T: Serialize>(&mut self, key: &str, value: &T) {
+        let value = ron::to_string(value)
+            .map_err(|e| e.to_string())
+            .and_then(|s| ron::from_str(&s).map_err(|e| e.to_string()));
+        match value {
+            Ok(value) => {
+                self.persistent.insert(key.to_string(), value);
+            }
+            Err(e) => warn!("Persistent value {key} can't be serialized: {e}"),
+        }
+    }
+
+    /// Returns None if the key doesn't exist or the value doesn't deserialize as `T`
+    pub fn get_persistent<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.persistent.get(key)?.clone().into_rust().ok()
+    }
+
+    /// Serializes the persistent values as a RON map
+    pub fn export_state(&self) -> String {
+        ron::ser::to_string_pretty(&self.persistent, default()).unwrap_or_default()
+    }
+
+    /// Loads values from `Pico::export_state()`, replacing existing values with the same key.
+    /// Keys that are no longer used are kept but otherwise ignored.
+    pub fn import_state(&mut self, state: &str) -> Result<(), ron::error::SpannedError> {
+        let values: BTreeMap<String, ron::Value> = ron::from_str(state)?;
+        self.persistent.extend(values);
+        Ok(())
+    }
+
+    /// Stores `item` as a template that can be fetched with `Pico::from_preset()`.
+    /// Registering an existing name replaces it. Presets persist across frames so they can be
+    /// registered once at startup, don't include a `parent` since item indices only last one frame.
+    pub fn register_preset(&mut self, name: &str, item: PicoItem) {
+        self.presets.insert(name.to_string(), item);
+    }
+
+    /// Returns a clone of the preset to customize, usually with struct update syntax:
+    /// `PicoItem { text: "OK".into(), parent: Some(panel), ..pico.from_preset("button") }`
+    /// Panics if `name` was not registered, see `Pico::try_from_preset()`.
+    pub fn from_preset(&self, name: &str) -> PicoItem {
+        let Some(item) = self.try_from_preset(name) else {
+            panic!("Tried to use preset {name} but it was not registered");
+        };
+        item
+    }
+
+    /// Like `Pico::from_preset()` but returns None if `name` was not registered:
+    /// `PicoItem { text: "OK".into(), ..pico.try_from_preset("button").unwrap_or_default() }`
+    pub fn try_from_preset(&self, name: &str) -> Option<PicoItem> {
+        self.presets.get(name).cloned()
+    }
+
     pub fn storage(&mut self) -> Option<&mut Option<Box<dyn std::any::Any + Send + Sync>>> {
         if let Some(item) = self.items.last() {
             if let Some(state_item) = self.state.get_mut(&item.spatial_id) {
@@ -772,6 +2752,23 @@ impl Pico {
             // Custom material is being used.
             return None;
         }
+        let blend_state = item
+            .style
+            .blend_state
+            .or(self.default_blend_state)
+            .unwrap_or(BlendState::ALPHA_BLENDING);
+        let pixel_perfect = self.pixel_perfect || item.style.pixel_perfect;
+        let inner_shadow = &item.style.inner_shadow;
+        let backdrop_blur = item.style.backdrop_blur.filter(|_| self.backdrop.is_some());
+        let scale_factor = self.scale_factor.max(f32::EPSILON);
+        // Snap to whole physical pixels in pixel perfect mode
+        let snap = |px: f32| {
+            if pixel_perfect {
+                (px * scale_factor).round() / scale_factor
+            } else {
+                px
+            }
+        };
         let uv_size = item.get_uv_size();
         let corner_radius = self.valp_y(item.style.corner_radius, uv_size) * self.window_size.y;
         let corner_radius0 =
@@ -782,19 +2779,41 @@ impl Pico {
             self.valp_y(item.style.multi_corner_radius.2, uv_size) * self.window_size.y;
         let corner_radius3 =
             self.valp_y(item.style.multi_corner_radius.3, uv_size) * self.window_size.y;
-        let border_width = self.valp_y(item.style.border_width, uv_size) * self.window_size.y;
+        let border_width = snap(self.valp_y(item.style.border_width, uv_size) * self.window_size.y);
         let nine_patch = item.style.nine_patch.unwrap_or((0, 0, 0, 0));
-        let material = RectangleMaterial {
+        let mut stops = item.style.gradient_stops.clone();
+        if stops.len() > MAX_GRADIENT_STOPS {
+            warn!(
+                "Only {} gradient stops are supported, the rest are ignored",
+                MAX_GRADIENT_STOPS
+            );
+            stops.truncate(MAX_GRADIENT_STOPS);
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut gradient_stops = [Vec4::ZERO; MAX_GRADIENT_STOPS];
+        let mut gradient_stop_positions = Vec4::ZERO;
+        for (i, (position, color)) in stops.iter().enumerate() {
+            gradient_stops[i] = (*color + item.style.background_color)
+                .as_linear_rgba_f32()
+                .into();
+            gradient_stop_positions[i] = *position;
+        }
+        let mut material = RectangleMaterial {
             material_settings: RectangleMaterialUniform {
                 // re-order for tl, tr, br, bl
                 corner_radius: vec4(
-                    corner_radius2 + corner_radius,
-                    corner_radius1 + corner_radius,
-                    corner_radius3 + corner_radius,
-                    corner_radius0 + corner_radius,
+                    snap(corner_radius2 + corner_radius),
+                    snap(corner_radius1 + corner_radius),
+                    snap(corner_radius3 + corner_radius),
+                    snap(corner_radius0 + corner_radius),
                 ),
                 edge_softness: self.valp_y(item.style.edge_softness, uv_size) * self.window_size.y,
                 border_thickness: border_width,
+                border_offset: match item.style.border_align {
+                    BorderAlign::Inside => 0.0,
+                    BorderAlign::Center => border_width * 0.5,
+                    BorderAlign::Outside => border_width,
+                },
                 border_softness: self.valp_y(item.style.border_softness, uv_size)
                     * self.window_size.y,
                 nine_patch: vec4(
@@ -810,14 +2829,143 @@ impl Pico {
                 background_color2: (item.style.background_gradient.1 + item.style.background_color)
                     .as_linear_rgba_f32()
                     .into(),
+                gradient_stops,
+                gradient_stop_positions,
+                gradient_stop_count: stops.len() as u32,
+                image_tiling: item.style.image_tiling.unwrap_or(Vec2::ZERO),
+                inner_shadow_color: inner_shadow
+                    .as_ref()
+                    .map_or(Vec4::ZERO, |s| s.color.as_linear_rgba_f32().into()),
+                inner_shadow_offset: inner_shadow.as_ref().map_or(Vec2::ZERO, |s| s.offset),
+                inner_shadow_blur: inner_shadow.as_ref().map_or(0.0, |s| {
+                    self.valp_y(s.blur, uv_size).max(0.0) * self.window_size.y
+                }),
+                inner_shadow_spread: inner_shadow
+                    .as_ref()
+                    .map_or(0.0, |s| self.valp_y(s.spread, uv_size) * self.window_size.y),
+                border_dash: (self.valp_x(item.style.border_dash.0, uv_size) * self.window_size.x)
+                    .max(0.0),
+                border_gap: (self.valp_x(item.style.border_dash.1, uv_size) * self.window_size.x)
+                    .max(0.0),
+                backdrop_blur: backdrop_blur.map_or(Vec2::ZERO, |blur| {
+                    blur.max(0.0) / self.window_size.max(Vec2::ONE)
+                }),
+                backdrop_alpha: 1.0,
                 background_mat: item.style.background_uv_transform.compute_matrix(),
-                flags: if item.style.image.is_some() { 1 } else { 0 },
+                clip: if let Some(clip) = item.clip {
+                    // To world space pixels, +y is up
+                    let min = (vec2(clip.x, clip.w) - 0.5) * vec2(1.0, -1.0) * self.window_size;
+                    let max = (vec2(clip.z, clip.y) - 0.5) * vec2(1.0, -1.0) * self.window_size;
+                    vec4(min.x, min.y, max.x, max.y)
+                } else {
+                    Vec4::ZERO
+                },
+                flags: if item.style.image.is_some() {
+                    MATERIAL_FLAGS_TEXTURE_BIT
+                } else {
+                    0
+                } | if item.clip.is_some() {
+                    MATERIAL_FLAGS_CLIP_BIT
+                } else {
+                    0
+                } | if blend_state == BlendState::PREMULTIPLIED_ALPHA_BLENDING {
+                    MATERIAL_FLAGS_PREMULTIPLIED_BIT
+                } else {
+                    0
+                } | if pixel_perfect {
+                    MATERIAL_FLAGS_PIXEL_PERFECT_BIT
+                } else {
+                    0
+                } | match item.style.gradient_kind {
+                    GradientKind::Linear => 0,
+                    GradientKind::Radial => MATERIAL_FLAGS_RADIAL_GRADIENT_BIT,
+                } | match item.style.border_style {
+                    BorderStyle::Solid => 0,
+                    BorderStyle::Dashed => MATERIAL_FLAGS_DASHED_BORDER_BIT,
+                    BorderStyle::Dotted => MATERIAL_FLAGS_DOTTED_BORDER_BIT,
+                } | if backdrop_blur.is_some() {
+                    MATERIAL_FLAGS_BACKDROP_BLUR_BIT
+                } else {
+                    0
+                },
             },
             texture: item.style.image.clone(),
-            blend_state: item.style.blend_state,
+            backdrop: backdrop_blur.and(self.backdrop.clone()),
+            blend_state: Some(blend_state),
         };
+        // Fades the item with its ancestors
+        let opacity = item.get_opacity();
+        let settings = &mut material.material_settings;
+        settings.border_color.w *= opacity;
+        settings.background_color1.w *= opacity;
+        settings.background_color2.w *= opacity;
+        for stop in &mut settings.gradient_stops {
+            stop.w *= opacity;
+        }
+        settings.inner_shadow_color.w *= opacity;
+        settings.backdrop_alpha *= opacity;
         Some(material)
     }
+
+    /// Material for `ItemStyle::shadow` and how many pixels the shadow's mesh extends past the item on
+    /// each side. The mesh is the item's size plus that on each side, offset by `Shadow::offset`.
+    pub fn get_shadow_material(
+        &mut self,
+        item: &ProcessedPicoItem,
+    ) -> Option<(RectangleMaterial, f32)> {
+        let shadow = item.style.shadow.clone()?;
+        let mut material = self.get_rect_material(item)?;
+        let uv_size = item.get_uv_size();
+        let blur = self.valp_y(shadow.blur, uv_size).max(0.0) * self.window_size.y;
+        let spread = self.valp_y(shadow.spread, uv_size) * self.window_size.y;
+        let grow = spread + blur * 0.5;
+        let settings = &mut material.material_settings;
+        let color: Vec4 = shadow.color.as_linear_rgba_f32().into();
+        // Grown by the spread so it follows the item's corners, at least half the blur so blurred
+        // corners are round
+        settings.corner_radius = (settings.corner_radius + spread).max(Vec4::ZERO) + blur * 0.5;
+        settings.edge_softness = blur.max(1.0);
+        settings.border_thickness = 0.0;
+        settings.border_offset = 0.0;
+        settings.background_color1 = color;
+        settings.background_color2 = color;
+        settings.background_color1.w *= item.get_opacity();
+        settings.background_color2.w *= item.get_opacity();
+        settings.gradient_stop_count = 0;
+        settings.inner_shadow_color = Vec4::ZERO;
+        settings.nine_patch = Vec4::ZERO;
+        settings.flags &= !(MATERIAL_FLAGS_TEXTURE_BIT
+            | MATERIAL_FLAGS_RADIAL_GRADIENT_BIT
+            | MATERIAL_FLAGS_BACKDROP_BLUR_BIT);
+        material.texture = None;
+        material.backdrop = None;
+        Some((material, grow))
+    }
+}
+
+/// Limits where a drag can move to, see `Pico::constrain_drag()`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum DragConstraint {
+    #[default]
+    None,
+    /// Only moves horizontally from where the drag started
+    X,
+    /// Only moves vertically from where the drag started
+    Y,
+    /// Stays within the bbox, uv of the window
+    Region(Vec4),
+}
+
+impl DragConstraint {
+    /// Constrain a cursor `position` for a drag that started at `start`, units uv of the window
+    pub fn apply(&self, start: Vec2, position: Vec2) -> Vec2 {
+        match self {
+            DragConstraint::None => position,
+            DragConstraint::X => vec2(position.x, start.y),
+            DragConstraint::Y => vec2(start.x, position.y),
+            DragConstraint::Region(bbox) => position.clamp(bbox.xy(), bbox.zw().max(bbox.xy())),
+        }
+    }
 }
 
 /// Units uv of the window
@@ -837,6 +2985,69 @@ impl Drag {
     pub fn total_delta(&self) -> Vec2 {
         self.end - self.start
     }
+    /// Logical pixels, +y down. `window_size` is usually `Pico::window_size`
+    pub fn delta_px(&self, window_size: Vec2) -> Vec2 {
+        self.delta() * window_size
+    }
+    /// Logical pixels, +y down. `window_size` is usually `Pico::window_size`
+    pub fn total_delta_px(&self, window_size: Vec2) -> Vec2 {
+        self.total_delta() * window_size
+    }
+}
+
+/// Copy `region` out of a 2d image, for example a screenshot with `Pico::screenshot_region()`.
+/// Returns None if the region is outside the image or the format is compressed.
+pub fn crop_image(image: &Image, region: URect) -> Option<Image> {
+    let bytes_per_pixel = image.texture_descriptor.format.block_copy_size(None)? as usize;
+    let image_size = image.texture_descriptor.size;
+    if region.max.x > image_size.width || region.max.y > image_size.height {
+        return None;
+    }
+    let row_bytes = image_size.width as usize * bytes_per_pixel;
+    let size = region.size();
+    let mut data = Vec::with_capacity(size.x as usize * size.y as usize * bytes_per_pixel);
+    for y in region.min.y..region.max.y {
+        let start = y as usize * row_bytes + region.min.x as usize * bytes_per_pixel;
+        data.extend_from_slice(&image.data[start..start + size.x as usize * bytes_per_pixel]);
+    }
+    Some(Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        image.texture_descriptor.format,
+        RenderAssetUsages::default(),
+    ))
+}
+
+/// Multiplies each item's `ItemStyle::opacity` with its parent's. Items are in the order they were added,
+/// so parents come before their children.
+pub(crate) fn accumulate_opacity(items: &mut [ProcessedPicoItem]) {
+    for i in 0..items.len() {
+        let parent_opacity = items[i]
+            .parent
+            .map_or(1.0, |parent| items[parent.0].opacity);
+        items[i].opacity = items[i].style.opacity * parent_opacity;
+    }
+}
+
+/// Works for either winding order
+pub fn point_in_convex_polygon(point: Vec2, polygon: &[Vec2]) -> bool {
+    let mut sign = 0.0;
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let cross = (b - *a).perp_dot(point - *a);
+        if cross != 0.0 {
+            if sign != 0.0 && cross.signum() != sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+    }
+    !polygon.is_empty()
 }
 
 pub fn get_bbox(size: Vec2, uv_position: Vec2, anchor: &Anchor) -> Vec4 {
@@ -845,3 +3056,101 @@ pub fn get_bbox(size: Vec2, uv_position: Vec2, anchor: &Anchor) -> Vec4 {
     let b = uv_position + half_size + size * -anchor.as_vec() * vec2(1.0, -1.0);
     vec4(a.x, a.y, b.x, b.y)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_get_out_of_range() {
+        let mut pico = Pico::default();
+        assert!(pico.try_get(&ItemIndex(5)).is_none());
+        let index = pico.add(PicoItem::default());
+        assert!(pico.try_get(&index).is_some());
+        assert!(pico.try_get(&ItemIndex(5)).is_none());
+        assert!(pico.try_get_mut(&ItemIndex(5)).is_none());
+    }
+
+    #[test]
+    fn try_from_preset_unregistered() {
+        let mut pico = Pico::default();
+        assert!(pico.try_from_preset("button").is_none());
+        pico.register_preset("button", PicoItem::new().text("OK"));
+        assert_eq!(pico.try_from_preset("button").unwrap().text, "OK");
+        assert_eq!(pico.from_preset("button").text, "OK");
+    }
+
+    #[test]
+    fn right_clicked_while_hovered() {
+        let mut pico = Pico::default();
+        let index = pico.add(PicoItem::default());
+        let mut input = ButtonInput::<MouseButton>::default();
+        input.press(MouseButton::Right);
+        pico.state.insert(
+            pico.get(&index).get_spatial_id(),
+            StateItem {
+                hover: true,
+                input: Some(input.clone()),
+                ..default()
+            },
+        );
+        assert!(pico.right_clicked(&index));
+        assert!(!pico.clicked(&index));
+        assert!(!pico.middle_clicked(&index));
+
+        // Next frame the button is released
+        input.clear();
+        input.release(MouseButton::Right);
+        pico.get_state_mut(&index).unwrap().input = Some(input);
+        assert!(!pico.right_clicked(&index));
+
+        // Not hovered
+        let state_item = pico.get_state_mut(&index).unwrap();
+        let mut input = ButtonInput::<MouseButton>::default();
+        input.press(MouseButton::Right);
+        state_item.input = Some(input);
+        state_item.hover = false;
+        assert!(!pico.right_clicked(&index));
+    }
+
+    #[test]
+    fn persistent_state_round_trip() {
+        let mut pico = Pico::default();
+        pico.set_persistent("collapsing/1", &true);
+        pico.set_persistent("scroll_area/2", &12);
+        let exported = pico.export_state();
+
+        let mut loaded = Pico::default();
+        loaded.set_persistent("scroll_area/2", &3);
+        loaded.import_state(&exported).unwrap();
+        assert_eq!(loaded.get_persistent::<bool>("collapsing/1"), Some(true));
+        assert_eq!(loaded.get_persistent::<i32>("scroll_area/2"), Some(12));
+        assert_eq!(loaded.get_persistent::<bool>("scroll_area/2"), None);
+        assert!(loaded.import_state("not ron (").is_err());
+    }
+
+    #[test]
+    fn opacity_fades_child_border() {
+        let mut pico = Pico::default();
+        let parent = pico.add(PicoItem {
+            style: ItemStyle {
+                opacity: 0.5,
+                ..default()
+            },
+            ..default()
+        });
+        let child = pico.add(PicoItem {
+            parent: Some(parent),
+            style: ItemStyle {
+                border_width: Val::Px(2.0),
+                border_color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+                ..default()
+            },
+            ..default()
+        });
+        accumulate_opacity(&mut pico.items);
+        let child = pico.get(&child).clone();
+        let material = pico.get_rect_material(&child).unwrap();
+        assert_eq!(material.material_settings.border_color.w, 0.4);
+    }
+}
@@ -14,7 +14,7 @@ use bevy::{
     sprite::{Material2d, Material2dKey, Material2dPlugin},
 };
 
-use crate::hash::hash_vec4;
+use crate::hash::{hash_vec2, hash_vec4};
 
 pub const RECTANGLE_MATERIAL_HANDLE: Handle<Shader> = Handle::weak_from_u128(9832747364550932847);
 
@@ -38,28 +38,88 @@ pub struct RectangleMaterialUniform {
     pub edge_softness: f32,
     pub border_thickness: f32,
     pub border_softness: f32,
+    /// Pixels the border extends outside the rect, see `BorderAlign`
+    pub border_offset: f32,
     pub nine_patch: Vec4,
     pub border_color: Vec4,
     pub background_color1: Vec4,
     pub background_color2: Vec4,
+    /// Linear rgba of each stop, used instead of background_color1/2 if `gradient_stop_count` > 0
+    pub gradient_stops: [Vec4; MAX_GRADIENT_STOPS],
+    /// Position of each stop along the gradient, in increasing order
+    pub gradient_stop_positions: Vec4,
+    pub gradient_stop_count: u32,
     pub background_mat: Mat4,
+    /// World space pixels: min x, min y, max x, max y. Only used if `MATERIAL_FLAGS_CLIP_BIT` is set.
+    pub clip: Vec4,
     pub flags: u32,
+    /// Times the texture repeats across the rect, zero for no tiling. See `ItemStyle::image_tiling`
+    pub image_tiling: Vec2,
+    /// Linear rgba, no inner shadow if the alpha is zero. See `ItemStyle::inner_shadow`
+    pub inner_shadow_color: Vec4,
+    /// Pixels, +y is down
+    pub inner_shadow_offset: Vec2,
+    /// Pixels
+    pub inner_shadow_blur: f32,
+    /// Pixels
+    pub inner_shadow_spread: f32,
+    /// Pixels, only used for dashed borders
+    pub border_dash: f32,
+    /// Pixels, only used for dashed and dotted borders
+    pub border_gap: f32,
+    /// Radius of the backdrop blur in uv of the screen, see `ItemStyle::backdrop_blur`
+    pub backdrop_blur: Vec2,
+    /// Multiplies the alpha of the blurred backdrop
+    pub backdrop_alpha: f32,
 }
 
+pub const MAX_GRADIENT_STOPS: usize = 4;
+
+pub const MATERIAL_FLAGS_TEXTURE_BIT: u32 = 1;
+pub const MATERIAL_FLAGS_CLIP_BIT: u32 = 2;
+/// Set when blending with `BlendState::PREMULTIPLIED_ALPHA_BLENDING`, the texture is expected to be premultiplied.
+pub const MATERIAL_FLAGS_PREMULTIPLIED_BIT: u32 = 4;
+/// Hard edges, skips the edge and border softness.
+pub const MATERIAL_FLAGS_PIXEL_PERFECT_BIT: u32 = 8;
+/// The gradient is mixed by distance from the center instead of top to bottom.
+pub const MATERIAL_FLAGS_RADIAL_GRADIENT_BIT: u32 = 16;
+/// Border is drawn as dashes of `border_dash` pixels separated by `border_gap`.
+pub const MATERIAL_FLAGS_DASHED_BORDER_BIT: u32 = 32;
+/// Border is drawn as round dots the border's thickness wide, separated by `border_gap`.
+pub const MATERIAL_FLAGS_DOTTED_BORDER_BIT: u32 = 64;
+/// The `backdrop` texture is blurred behind the background.
+pub const MATERIAL_FLAGS_BACKDROP_BLUR_BIT: u32 = 128;
+
 impl core::hash::Hash for RectangleMaterialUniform {
     fn hash<H: Hasher>(&self, state: &mut H) {
         hash_vec4(&self.corner_radius, state);
         self.edge_softness.to_bits().hash(state);
         self.border_thickness.to_bits().hash(state);
         self.border_softness.to_bits().hash(state);
+        self.border_offset.to_bits().hash(state);
         hash_vec4(&self.border_color, state);
         hash_vec4(&self.background_color1, state);
         hash_vec4(&self.background_color2, state);
+        for stop in &self.gradient_stops {
+            hash_vec4(stop, state);
+        }
+        hash_vec4(&self.gradient_stop_positions, state);
+        self.gradient_stop_count.hash(state);
         hash_vec4(&self.background_mat.x_axis, state);
         hash_vec4(&self.background_mat.y_axis, state);
         hash_vec4(&self.background_mat.z_axis, state);
         hash_vec4(&self.background_mat.w_axis, state);
+        hash_vec4(&self.clip, state);
         self.flags.hash(state);
+        hash_vec2(&self.image_tiling, state);
+        hash_vec4(&self.inner_shadow_color, state);
+        hash_vec2(&self.inner_shadow_offset, state);
+        self.inner_shadow_blur.to_bits().hash(state);
+        self.inner_shadow_spread.to_bits().hash(state);
+        self.border_dash.to_bits().hash(state);
+        self.border_gap.to_bits().hash(state);
+        hash_vec2(&self.backdrop_blur, state);
+        self.backdrop_alpha.to_bits().hash(state);
     }
 }
 
@@ -71,6 +131,10 @@ pub struct RectangleMaterial {
     #[texture(1)]
     #[sampler(2)]
     pub texture: Option<Handle<Image>>,
+    /// Sampled in screen space when `MATERIAL_FLAGS_BACKDROP_BLUR_BIT` is set, see `Pico::backdrop`
+    #[texture(3)]
+    #[sampler(4)]
+    pub backdrop: Option<Handle<Image>>,
     pub blend_state: Option<BlendState>,
 }
 
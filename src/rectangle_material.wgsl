@@ -7,18 +7,42 @@
 #import bevy_render::instance_index::get_instance_index
 
 const MATERIAL_FLAGS_TEXTURE_BIT: u32 = 1u;
+const MATERIAL_FLAGS_CLIP_BIT: u32 = 2u;
+const MATERIAL_FLAGS_PREMULTIPLIED_BIT: u32 = 4u;
+const MATERIAL_FLAGS_PIXEL_PERFECT_BIT: u32 = 8u;
+const MATERIAL_FLAGS_RADIAL_GRADIENT_BIT: u32 = 16u;
+const MATERIAL_FLAGS_DASHED_BORDER_BIT: u32 = 32u;
+const MATERIAL_FLAGS_DOTTED_BORDER_BIT: u32 = 64u;
+const MATERIAL_FLAGS_BACKDROP_BLUR_BIT: u32 = 128u;
+// Taps along each axis of the backdrop box blur
+const BACKDROP_BLUR_TAPS: i32 = 7;
 
 struct CustomMaterial {
     corner_radius: vec4<f32>,
     edge_softness: f32,
     border_thickness: f32,
     border_softness: f32,
+    // Distance the border extends outside the rect, the mesh is grown by this on each side
+    border_offset: f32,
     nine_patch: vec4<f32>,
     border_color: vec4<f32>,
     background_color1: vec4<f32>,
     background_color2: vec4<f32>,
+    gradient_stops: array<vec4<f32>, 4>,
+    gradient_stop_positions: vec4<f32>,
+    gradient_stop_count: u32,
     background_mat: mat4x4<f32>,
+    clip: vec4<f32>,
     flags: u32,
+    image_tiling: vec2<f32>,
+    inner_shadow_color: vec4<f32>,
+    inner_shadow_offset: vec2<f32>,
+    inner_shadow_blur: f32,
+    inner_shadow_spread: f32,
+    border_dash: f32,
+    border_gap: f32,
+    backdrop_blur: vec2<f32>,
+    backdrop_alpha: f32,
 };
 
 @group(2) @binding(0)
@@ -27,6 +51,10 @@ var<uniform> m: CustomMaterial;
 var texture: texture_2d<f32>;
 @group(2) @binding(2)
 var texture_sampler: sampler;
+@group(2) @binding(3)
+var backdrop: texture_2d<f32>;
+@group(2) @binding(4)
+var backdrop_sampler: sampler;
 
 
 fn rounded_box_sdf(center: vec2<f32>, size: vec2<f32>, radius: vec4<f32>) -> f32 {
@@ -37,6 +65,77 @@ fn rounded_box_sdf(center: vec2<f32>, size: vec2<f32>, radius: vec4<f32>) -> f32
     return min(max(q.x, q.y), 0.0) + length(max(q, vec2(0.0))) - r.x;
 }
 
+// Distance along the perimeter of a rounded rect centered on the origin, clockwise from the start of
+// the top edge. `radius` is the corner radius of the quadrant `center` is in, which is assumed for all
+// corners, so the dash pattern can be uneven where different radii meet.
+fn perimeter_position(center: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let inner = half_size - radius;
+    let w = 2.0 * inner;
+    let arc = 1.5707963 * radius;
+    let a = abs(center);
+    if a.x > inner.x && a.y > inner.y {
+        let d = center - sign(center) * inner;
+        if center.x > 0.0 && center.y < 0.0 {
+            return w.x + radius * atan2(d.x, -d.y);
+        } else if center.x > 0.0 {
+            return w.x + arc + w.y + radius * atan2(d.y, d.x);
+        } else if center.y > 0.0 {
+            return 2.0 * w.x + w.y + 2.0 * arc + radius * atan2(-d.x, d.y);
+        }
+        return 2.0 * w.x + 2.0 * w.y + 3.0 * arc + radius * atan2(-d.y, -d.x);
+    }
+    if half_size.y - a.y < half_size.x - a.x {
+        if center.y < 0.0 {
+            return center.x + inner.x;
+        }
+        return 2.0 * arc + w.x + w.y + inner.x - center.x;
+    }
+    if center.x > 0.0 {
+        return w.x + arc + center.y + inner.y;
+    }
+    return 2.0 * w.x + w.y + 3.0 * arc + inner.y - center.y;
+}
+
+// Coverage of the dash or dot pattern at `s` along the border, `across` is the distance from the
+// middle of the border band
+fn border_pattern(s: f32, across: f32) -> f32 {
+    if ((m.flags & MATERIAL_FLAGS_DOTTED_BORDER_BIT) != 0u) {
+        let dot = m.border_thickness;
+        let period = max(dot + m.border_gap, 1e-5);
+        let along = (s - 0.5 * dot) - period * round((s - 0.5 * dot) / period);
+        return saturate(0.5 - (length(vec2(along, across)) - 0.5 * dot));
+    }
+    let period = max(m.border_dash + m.border_gap, 1e-5);
+    let t = s - period * floor(s / period);
+    return saturate(0.5 + max(min(t, m.border_dash - t), t - period));
+}
+
+// Box blur of the backdrop around `screen_uv`, the taps are spread over `m.backdrop_blur` on each side
+fn blurred_backdrop(screen_uv: vec2<f32>) -> vec3<f32> {
+    var sum = vec3(0.0);
+    let half_taps = f32(BACKDROP_BLUR_TAPS - 1) * 0.5;
+    for (var y = 0; y < BACKDROP_BLUR_TAPS; y = y + 1) {
+        for (var x = 0; x < BACKDROP_BLUR_TAPS; x = x + 1) {
+            let offset = (vec2(f32(x), f32(y)) - half_taps) / half_taps * m.backdrop_blur;
+            sum = sum + textureSampleLevel(backdrop, backdrop_sampler, screen_uv + offset, 0.0).rgb;
+        }
+    }
+    return sum / f32(BACKDROP_BLUR_TAPS * BACKDROP_BLUR_TAPS);
+}
+
+// Interpolates between the two stops around t, clamped to the first and last stop
+fn gradient_stop_color(t: f32) -> vec4<f32> {
+    var color = m.gradient_stops[0];
+    for (var i = 1u; i < m.gradient_stop_count; i = i + 1u) {
+        let p0 = m.gradient_stop_positions[i - 1u];
+        let p1 = m.gradient_stop_positions[i];
+        if t > p0 {
+            color = mix(m.gradient_stops[i - 1u], m.gradient_stops[i], saturate((t - p0) / max(p1 - p0, 1e-5)));
+        }
+    }
+    return color;
+}
+
 // ------------------------------
 // ------------------------------
 // ------------------------------
@@ -124,30 +223,57 @@ fn vertex(vertex: Vertex) -> VertexOutput {
 
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    if ((m.flags & MATERIAL_FLAGS_CLIP_BIT) != 0u) {
+        let p = in.world_position.xy;
+        if any(p < m.clip.xy) || any(p > m.clip.zw) {
+            discard;
+        }
+    }
+
     var border_thickness = m.border_thickness;
 
-    let bg_uv = (m.background_mat * vec4(in.uv - 0.5, 0.0, 1.0)).xy + 0.5;
+    let model = mesh[in.instance_index].model;
 
-    var background_color = mix(m.background_color1, m.background_color2, bg_uv.y);
+    let scaleX = length(model[0].xyz);
+    let scaleY = length(model[1].xyz);
+    let right = length(normalize(model[0].xyz));
+    let up = length(normalize(model[1].xyz));
 
+    // mesh is 1x1 so the x and y scale is the full size of the mesh, the rect is inset by border_offset
+    let mesh_size = vec2(scaleX / right, scaleY / up);
+    let size = mesh_size - 2.0 * m.border_offset;
+    let uv = (in.uv * mesh_size - m.border_offset) / size;
 
+    let bg_uv = (m.background_mat * vec4(uv - 0.5, 0.0, 1.0)).xy + 0.5;
 
+    var gradient_t = bg_uv.y;
+    if ((m.flags & MATERIAL_FLAGS_RADIAL_GRADIENT_BIT) != 0u) {
+        // 0 at the center, 1 at the middle of each edge
+        gradient_t = saturate(length(bg_uv - 0.5) * 2.0);
+    }
+    var background_color = mix(m.background_color1, m.background_color2, gradient_t);
+    if m.gradient_stop_count > 0u {
+        background_color = gradient_stop_color(gradient_t);
+    }
 
-    // Softening the border makes it larger, compensate for that
-    border_thickness = max(border_thickness - m.border_softness, 0.0);
+    let premultiplied = (m.flags & MATERIAL_FLAGS_PREMULTIPLIED_BIT) != 0u;
+    if premultiplied {
+        // The texture is already premultiplied, so only the color needs it
+        background_color = vec4(background_color.rgb * background_color.a, background_color.a);
+    }
 
-    let model = mesh[in.instance_index].model;
 
-    let scaleX = length(model[0].xyz);
-    let scaleY = length(model[1].xyz);
-    let right = length(normalize(model[0].xyz));
-    let up = length(normalize(model[1].xyz));
 
-    // mesh is 1x1 so the x and y scale is the full size of the rect
-    let size = vec2(scaleX / right, scaleY / up); 
+
+    // Softening the border makes it larger, compensate for that
+    border_thickness = max(border_thickness - m.border_softness, 0.0);
 
     if ((m.flags & MATERIAL_FLAGS_TEXTURE_BIT) != 0u) {
-        if all(m.nine_patch == vec4(0.0)) {
+        if any(m.image_tiling != vec2(0.0)) {
+            // Gradients of the continuous uv so mip selection doesn't jump at the tile edges
+            let tiled_uv = bg_uv * m.image_tiling;
+            background_color = background_color * textureSampleGrad(texture, texture_sampler, fract(tiled_uv), dpdx(tiled_uv), dpdy(tiled_uv));
+        } else if all(m.nine_patch == vec4(0.0)) {
             background_color = background_color * textureSample(texture, texture_sampler, bg_uv);
         } else {
             let dims = vec2<f32>(textureDimensions(texture).xy);
@@ -170,6 +296,21 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
         }
     }
 
+    if ((m.flags & MATERIAL_FLAGS_BACKDROP_BLUR_BIT) != 0u) {
+        // The background is drawn over the blurred backdrop
+        let viewport = view.viewport;
+        let screen_uv = (in.position.xy - viewport.xy) / viewport.zw;
+        let backdrop_alpha = m.backdrop_alpha;
+        let backdrop_rgb = blurred_backdrop(screen_uv) * backdrop_alpha;
+        if premultiplied {
+            background_color = background_color + (1.0 - background_color.a) * vec4(backdrop_rgb, backdrop_alpha);
+        } else {
+            let alpha = background_color.a + backdrop_alpha * (1.0 - background_color.a);
+            let rgb = background_color.rgb * background_color.a + backdrop_rgb * (1.0 - background_color.a);
+            background_color = vec4(rgb / max(alpha, 1e-5), alpha);
+        }
+    }
+
     let min_edge = min(size.x, size.y);
     let radius_limit = min_edge * 0.5;
     let r = min(m.corner_radius, vec4(radius_limit));
@@ -180,14 +321,48 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     let r_off = (1.0 - saturate(max(max(max(r.x, r.y), r.z), r.w) * min_edge));
     let main_softness_offset = max(max(m.border_softness, m.edge_softness), 0.0) - r_off - 0.5;
 
-    let pos = in.uv.xy * size;
+    let pos = uv * size;
 
     var distance = rounded_box_sdf(pos - (size * 0.5), size * 0.5, r);
+    // The border's outer edge
+    let border_distance = distance - m.border_offset;
+
+    if m.inner_shadow_color.a > 0.0 {
+        // Shadowed outside of the rect shrunk by the spread and moved by the offset
+        let blur = max(m.inner_shadow_blur, 1.0);
+        let spread = m.inner_shadow_spread;
+        let lit_distance = rounded_box_sdf(pos - (size * 0.5) - m.inner_shadow_offset, max(size * 0.5 - spread, vec2(0.0)), max(r - spread, vec4(0.0)));
+        let shadow_alpha = m.inner_shadow_color.a * smoothstep(-0.5 * blur, 0.5 * blur, lit_distance);
+        let shadow_rgb = m.inner_shadow_color.rgb * shadow_alpha;
+        if premultiplied {
+            background_color = vec4(shadow_rgb, shadow_alpha) + (1.0 - shadow_alpha) * background_color;
+        } else {
+            // Over, with straight alpha
+            let alpha = shadow_alpha + background_color.a * (1.0 - shadow_alpha);
+            let rgb = shadow_rgb + background_color.rgb * background_color.a * (1.0 - shadow_alpha);
+            background_color = vec4(rgb / max(alpha, 1e-5), alpha);
+        }
+    }
 
-    let main_alpha = 1.0 - smoothstep(0.0, m.edge_softness, distance + main_softness_offset);
-    let a = 1.0 - smoothstep(0.0, m.border_softness, -distance - border_thickness - m.border_softness);
-    let b = 1.0 - smoothstep(0.0, m.border_softness, distance + m.border_softness);
-    let border_alpha = saturate(a * b * f32(m.border_thickness > 0.0));
+    var main_alpha = 0.0;
+    var border_alpha = 0.0;
+    if ((m.flags & MATERIAL_FLAGS_PIXEL_PERFECT_BIT) != 0u) {
+        // Hard edges, each pixel is either inside or outside
+        main_alpha = f32(distance < 0.0);
+        border_alpha = f32(border_distance < 0.0 && border_distance >= -m.border_thickness);
+    } else {
+        main_alpha = 1.0 - smoothstep(0.0, m.edge_softness, distance + main_softness_offset);
+        let a = 1.0 - smoothstep(0.0, m.border_softness, -border_distance - border_thickness - m.border_softness);
+        let b = 1.0 - smoothstep(0.0, m.border_softness, border_distance + m.border_softness);
+        border_alpha = saturate(a * b * f32(m.border_thickness > 0.0));
+    }
+    if ((m.flags & (MATERIAL_FLAGS_DASHED_BORDER_BIT | MATERIAL_FLAGS_DOTTED_BORDER_BIT)) != 0u) {
+        let center = pos - size * 0.5;
+        let qr = vec4(select(r.zw, r.xy, center.x > 0.0), r.w, r.z);
+        let s = perimeter_position(center, size * 0.5, select(qr.y, qr.x, center.y > 0.0));
+        border_alpha *= border_pattern(s, border_distance + 0.5 * m.border_thickness);
+    }
+    border_alpha *= m.border_color.a;
 
 
     //color = mix(color, m.border_color, border_alpha);
@@ -1,15 +1,32 @@
+use ab_glyph::{Font as _, ScaleFont};
 use bevy::{
-    math::{vec2, Vec3Swizzles, Vec4Swizzles},
+    a11y::{
+        accesskit::{self, NodeBuilder, Role},
+        AccessibilityNode, AccessibilityRequested,
+    },
+    ecs::query::QuerySingleError,
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    math::{vec2, vec4, Vec3Swizzles, Vec4Swizzles},
     prelude::*,
+    render::{
+        camera::RenderTarget,
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        view::RenderLayers,
+    },
     sprite::{Anchor, MaterialMesh2dBundle, Mesh2dHandle},
-    text::{BreakLineOn, Text2dBounds},
+    text::{BreakLineOn, Text2dBounds, TextLayoutInfo},
     utils::HashMap,
+    window::{PrimaryWindow, WindowRef},
 };
 use core::hash::Hasher;
-use std::{collections::hash_map::DefaultHasher, hash::Hash};
+use std::{collections::hash_map::DefaultHasher, hash::Hash, marker::PhantomData, sync::Arc};
 
 use crate::{
-    pico::{get_bbox, Drag, Pico, Pico2dCamera, StateItem},
+    pico::{
+        accumulate_opacity, get_bbox, point_in_convex_polygon, Drag, PicoResource, PicoTheme,
+        ProcessedPicoItem, StateItem, DEFAULT_DOUBLE_CLICK_TIME, DEFAULT_TOOLTIP_DELAY,
+    },
     rectangle_material::RectangleMaterial,
     MeshHandles, SwapMaterialEntity,
 };
@@ -19,42 +36,113 @@ pub struct PicoEntity {
     pub spatial_id: u64,
     pub anchor: Anchor,
     pub size: Vec2,
+    /// Pixels the interactive area extends past `size` on each side
+    pub hit_padding: Vec2,
+    /// Child entities, updated in place when only the window size changed
+    pub shadow: Option<Entity>,
+    pub background: Option<Entity>,
+    pub text: Option<Entity>,
+}
+
+/// Which entry of `PicoItem::sections` each section of a text entity came from, emoji can be split
+/// into sections of their own. Used to find span bounds for `Pico::text_links()`.
+#[derive(Component)]
+pub struct TextSpans {
+    pub spatial_id: u64,
+    pub sections: Vec<usize>,
+}
+
+/// Which Pico instance spawned the entity, so instances only manage their own entities
+#[derive(Component)]
+pub struct PicoOwner<P: PicoResource>(PhantomData<P>);
+
+impl<P: PicoResource> Default for PicoOwner<P> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
 }
 
 pub const MAJOR_DEPTH_AUTO_STEP: f32 = 0.000001;
 pub const MINOR_DEPTH_AUTO_STEP: f32 = 0.0000001;
+/// Draw z offset for each step of `ItemStyle::render_priority`
+pub const RENDER_PRIORITY_STEP: f32 = 0.001;
+/// Offsets of the copies drawn for `ItemStyle::text_outline`, scaled by the outline width
+const TEXT_OUTLINE_DIRECTIONS: [Vec2; 8] = [
+    Vec2::new(1.0, 0.0),
+    Vec2::new(-1.0, 0.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(0.0, -1.0),
+    Vec2::new(0.70710677, 0.70710677),
+    Vec2::new(-0.70710677, 0.70710677),
+    Vec2::new(0.70710677, -0.70710677),
+    Vec2::new(-0.70710677, -0.70710677),
+];
+/// Outline copies are drawn this far behind their text, and in front of the background
+const TEXT_OUTLINE_Z: f32 = -0.00005;
 
-#[allow(clippy::too_many_arguments)]
-pub fn render(
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn render<P: PicoResource>(
     mut commands: Commands,
     mut materials: ResMut<Assets<RectangleMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mesh_handles: Res<MeshHandles>,
+    fonts: Res<Assets<Font>>,
     time: Res<Time>,
-    camera: Query<(&Camera, &GlobalTransform), With<Pico2dCamera>>,
-    windows: Query<&Window>,
-    mut pico: ResMut<Pico>,
-    mut pico_entites: Query<(Entity, &mut Transform, &PicoEntity)>,
+    camera: Query<(&Camera, &GlobalTransform, Option<&RenderLayers>), With<P::Camera>>,
+    windows: Query<(&Window, Has<PrimaryWindow>)>,
+    mut pico_resource: ResMut<P>,
+    mut pico_entites: Query<(Entity, &mut Transform, &mut PicoEntity), With<PicoOwner<P>>>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
-    mut currently_dragging: Local<bool>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    (mut scratch, mut warned_multiple_cameras): (Local<RenderScratch>, Local<bool>),
+    accessibility_requested: Option<Res<AccessibilityRequested>>,
 ) {
-    let Ok((camera, camera_transform)) = camera.get_single() else {
-        return;
+    let (camera, camera_transform, camera_layers) = match camera.get_single() {
+        Ok(camera) => camera,
+        Err(QuerySingleError::NoEntities(_)) => return,
+        Err(QuerySingleError::MultipleEntities(_)) => {
+            if !*warned_multiple_cameras {
+                *warned_multiple_cameras = true;
+                error!(
+                    "{} cameras found with {}, nothing will be rendered for {}. Each Pico renders to a \
+                    single camera, use a PicoInstance<M> with PicoCamera<M> for each additional camera.",
+                    camera.iter().count(),
+                    std::any::type_name::<P::Camera>(),
+                    std::any::type_name::<P>(),
+                );
+            }
+            return;
+        }
     };
-    let Ok(window) = windows.get_single() else {
+    let Some(window) = camera_window(camera, &windows) else {
         return;
     };
+    // Entities are only drawn by cameras with the same layers, so each instance is only drawn by its own
+    let layers = camera_layers.copied().unwrap_or_default();
+    let pico = pico_resource.pico_mut();
     let window_size = Vec2::new(window.width(), window.height());
+    let scale_factor = window.scale_factor();
+    // See `PicoItem::scale_with_distance`
+    let distance_scale = |item: &ProcessedPicoItem| {
+        item.scale_with_distance
+            .zip(item.position_3d)
+            .map_or(1.0, |(scale, position)| {
+                scale.scale(camera_transform.translation().distance(position))
+            })
+    };
 
-    *currently_dragging = false;
+    let mut currently_dragging = false;
     let mut interacting = false;
     // Age all the state items
     for (_, state_item) in pico.state.iter_mut() {
         state_item.life -= time.delta_seconds();
         state_item.hover = false;
         state_item.input = None;
+        state_item.double_clicked = false;
         if mouse_button_input.pressed(MouseButton::Left) {
             if state_item.drag.is_some() {
-                *currently_dragging = true;
+                currently_dragging = true;
                 interacting = true;
             }
         } else {
@@ -62,14 +150,74 @@ pub fn render(
         }
     }
 
+    // Pop stacks whose guards were dropped so justified stacks are applied
+    pico.update_stack();
     let mut items = std::mem::take(&mut pico.items);
 
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        // Insertion order, before sorting by depth
+        let order = items
+            .iter()
+            .filter(|item| item.interactable && item.focusable && item.position_3d.is_none())
+            .map(|item| item.get_spatial_id())
+            .collect::<Vec<_>>();
+        let back = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        let current = pico
+            .focused
+            .and_then(|focused| order.iter().position(|id| *id == focused));
+        let next = match (current, back) {
+            (Some(i), false) => Some((i + 1) % order.len()),
+            (Some(i), true) => Some((i + order.len() - 1) % order.len()),
+            (None, false) => (!order.is_empty()).then_some(0),
+            (None, true) => order.len().checked_sub(1),
+        };
+        if let Some(next) = next {
+            pico.focused = Some(order[next]);
+        }
+    }
+
+    // Fade 3d items whose position is hidden by an occluder, before their opacity is passed down
+    let occluders = std::mem::take(&mut pico.occluders);
+    let camera_position = camera_transform.translation();
+    for item in &mut items {
+        let (Some(occluded_opacity), Some(position)) = (item.occluded_opacity, item.position_3d)
+        else {
+            continue;
+        };
+        if occluders
+            .iter()
+            .any(|occluder| occluder.blocks(camera_position, position))
+        {
+            item.style.opacity *= occluded_opacity;
+            if occluded_opacity <= 0.0 {
+                item.interactable = false;
+            }
+        }
+    }
+
+    // Before sorting, while parents are still before their children
+    accumulate_opacity(&mut items);
+
     // Sort so we interact in z order.
-    items.sort_by(|a, b| b.get_depth().partial_cmp(&a.get_depth()).unwrap());
+    items.sort_by(|a, b| front_to_back(a.get_depth(), b.get_depth()));
+
+    let mut item_positions = std::mem::take(&mut scratch.item_positions);
+    item_positions.clear();
 
-    let mut item_positions = Vec::new();
+    // Regions from other UI systems that pico shouldn't be interacted with under
+    let blocking_regions = std::mem::take(&mut pico.blocking_regions);
+    let blocked = window.cursor_position().is_some_and(|cursor_pos| {
+        let cursor_uv = cursor_pos / window_size;
+        blocking_regions.iter().any(|rect| rect.contains(cursor_uv))
+    });
+    // Items behind a modal backdrop can't be interacted with
+    let modal_depth = pico.modal_depth.take().unwrap_or(f32::NEG_INFINITY);
 
     let mut first_interact_found = false;
+    let mut clicked_spatial_id = None;
+    // Items are in front to back order, so the front most tooltip is used
+    let mut tooltip = None;
+    let world_to_ndc = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
     for item in &mut items {
         if item.id.is_none() {
             item.id = Some(item.generate_id());
@@ -81,25 +229,33 @@ pub fn render(
             .extend(item.get_depth());
 
         if let Some(position_3d) = item.position_3d {
-            item_ndc = camera
-                .world_to_ndc(camera_transform, position_3d)
-                .unwrap_or(Vec3::NAN);
+            let Some(ndc) = position_3d_ndc(world_to_ndc, position_3d) else {
+                // Skip rendering and interaction this frame, the entity is removed if it existed
+                item_positions.push(Vec3::NAN);
+                continue;
+            };
+            item_ndc = ndc;
             // include 2d offset
             item_ndc += ((item.get_uv_position()) * vec2(2.0, -2.0)).extend(item.get_depth());
         }
 
+        // Only the drawn z is offset, interaction uses the sorted depth
+        let draw_z = item_ndc.z + item.style.render_priority as f32 * RENDER_PRIORITY_STEP;
         let item_pos = item_ndc.xy() * window_size * 0.5;
-        item_positions.push(item_pos.extend(item_ndc.z));
+        item_positions.push(item_pos.extend(draw_z));
 
         if let Some(existing_state_item) = pico.state.get_mut(&spatial_id) {
             // If a item in the state matches one created this frame keep it around
             existing_state_item.life = existing_state_item.life.max(0.0);
-            let Ok((_, mut trans, pico_entity)) =
-                pico_entites.get_mut(existing_state_item.entity.unwrap())
+            // State created by `Pico::animate()` may not have an entity yet
+            let Some(Ok((_, mut trans, pico_entity))) = existing_state_item
+                .entity
+                .map(|entity| pico_entites.get_mut(entity))
             else {
                 continue;
             };
-            trans.translation = item_pos.extend(item_ndc.z);
+            trans.translation = item_pos.extend(draw_z);
+            trans.scale = Vec3::splat(distance_scale(item));
 
             if !existing_state_item.interactable {
                 continue;
@@ -109,18 +265,33 @@ pub fn render(
                 if mouse_button_input.pressed(MouseButton::Left) && !first_interact_found {
                     if let Some(drag) = &mut existing_state_item.drag {
                         drag.last_frame = drag.end;
-                        drag.end = cursor_pos / window_size;
+                        drag.end = existing_state_item
+                            .drag_constraint
+                            .apply(drag.start, cursor_pos / window_size);
                     }
                 }
                 existing_state_item.bbox = get_bbox(
-                    pico_entity.size / window_size,
+                    pico_entity.size * trans.scale.x / window_size,
                     trans.translation.xy() / window_size * vec2(1.0, -1.0) + 0.5,
                     &pico_entity.anchor,
                 );
-                let xy = existing_state_item.bbox.xy() * window_size;
-                let zw = existing_state_item.bbox.zw() * window_size;
-                if cursor_pos.cmpge(xy).all() && cursor_pos.cmple(zw).all() {
+                let hit_padding = pico_entity.hit_padding * trans.scale.x;
+                let xy = existing_state_item.bbox.xy() * window_size - hit_padding;
+                let zw = existing_state_item.bbox.zw() * window_size + hit_padding;
+                if cursor_pos.cmpge(xy).all()
+                    && cursor_pos.cmple(zw).all()
+                    && (item.get_polygon().is_empty()
+                        || point_in_convex_polygon(cursor_pos / window_size, item.get_polygon()))
+                    && item.in_clip(cursor_pos / window_size)
+                    && !blocked
+                    && item.get_depth() >= modal_depth
+                {
                     existing_state_item.hover = true;
+                    existing_state_item.hover_time += time.delta_seconds();
+                    let tooltip_delay = pico.tooltip_delay.unwrap_or(DEFAULT_TOOLTIP_DELAY);
+                    if tooltip.is_none() && existing_state_item.hover_time >= tooltip_delay {
+                        tooltip = item.style.tooltip.clone();
+                    }
                     if !first_interact_found {
                         existing_state_item.input = Some(mouse_button_input.clone());
                         if mouse_button_input.any_just_pressed([
@@ -131,8 +302,20 @@ pub fn render(
                             interacting = true;
                             first_interact_found = true;
                         }
+                        if mouse_button_input.just_pressed(MouseButton::Left) {
+                            clicked_spatial_id = item.focusable.then_some(spatial_id);
+                            let now = time.elapsed_seconds();
+                            let threshold =
+                                pico.double_click_time.unwrap_or(DEFAULT_DOUBLE_CLICK_TIME);
+                            let double_clicked = existing_state_item
+                                .last_click_time
+                                .is_some_and(|last| now - last <= threshold);
+                            existing_state_item.double_clicked = double_clicked;
+                            existing_state_item.last_click_time =
+                                if double_clicked { None } else { Some(now) };
+                        }
                         if mouse_button_input.just_pressed(MouseButton::Left)
-                            && !*currently_dragging
+                            && !currently_dragging
                             && existing_state_item.drag.is_none()
                         {
                             let cursor_uv_pos = cursor_pos / window_size;
@@ -147,10 +330,40 @@ pub fn render(
             }
         }
     }
-    let mut cached_materials = MaterialCache::default();
+
+    for (_, state_item) in pico.state.iter_mut() {
+        if !state_item.hover {
+            state_item.hover_time = 0.0;
+        }
+    }
+    pico.tooltip = tooltip;
+
+    // Focus follows the mouse, but the outline is only shown after keyboard input
+    if mouse_button_input.just_pressed(MouseButton::Left) {
+        pico.focused = clicked_spatial_id;
+        pico.focus_visible = false;
+    }
+    if keyboard_input.get_just_pressed().next().is_some() {
+        pico.focus_visible = true;
+    }
+
+    let mut cached_materials = std::mem::take(&mut scratch.cached_materials);
+
+    pico.time_since_update += time.delta_seconds();
+    let rebuild = if let Some(update_interval) = pico.update_interval {
+        pico.time_since_update >= update_interval
+    } else {
+        true
+    };
+    if rebuild {
+        pico.time_since_update = 0.0;
+    }
 
     // It seems that we need to add things in z order for them to show up in that order initially
     for (item, item_pos) in items.iter_mut().zip(item_positions.iter()) {
+        if !item_pos.is_finite() {
+            continue;
+        }
         let spatial_id = item.get_spatial_id();
 
         let generate = if let Some(existing_state_item) = pico.state.get_mut(&spatial_id) {
@@ -160,118 +373,403 @@ pub fn render(
         };
         let material = pico.get_rect_material(item);
 
-        if generate || pico.window_size != window_size {
+        if (generate && rebuild) || pico.window_size != window_size {
             let size = item.get_uv_size() * window_size;
-            let font_size = pico.valp_y(item.style.font_size, item.get_uv_size()) * window_size.y;
+            let shadow_material = pico.get_shadow_material(item);
+            let mut font_size =
+                pico.valp_y(item.style.font_size, item.get_uv_size()) * window_size.y;
+            if item.style.crisp_text {
+                // Whole physical pixel font sizes rasterize without resampling
+                font_size = (font_size * scale_factor).round().max(1.0) / scale_factor;
+            }
+            let baseline_px = item
+                .style
+                .text_baseline
+                .map(|baseline| pico.valp_y(baseline, item.get_uv_size()) * window_size.y);
+            let has_border = pico.valp_x(item.style.border_width, item.get_uv_size()) > 0.0;
+            let hit_padding = vec2(
+                pico.valp_x(item.style.hit_padding, item.get_uv_size()),
+                pico.valp_y(item.style.hit_padding, item.get_uv_size()),
+            ) * window_size;
+
+            if pico.warn_default_font && item.has_text() && item.style.font == Handle::default() {
+                let text = item.get_text();
+                if !pico.default_font_warned.contains(&text) {
+                    warn!(
+                        "Pico item with text \"{}\" is using the default font, which is missing many glyphs",
+                        text
+                    );
+                    pico.default_font_warned.insert(text);
+                }
+            }
+
+            let (sections, spans) = text_sections(item, font_size);
+            let text_spans = TextSpans {
+                spatial_id,
+                sections: spans,
+            };
+            let text = Text {
+                sections,
+                justify: item.style.justify,
+                linebreak_behavior: BreakLineOn::WordBoundary,
+            };
+            // Text colored with the outline color and the pixel offset of each copy
+            let outline = item.style.text_outline.map(|(color, width)| {
+                let mut outline_text = text.clone();
+                for section in &mut outline_text.sections {
+                    section.style.color = color.with_a(color.a() * item.get_opacity());
+                }
+                let width = pico.valp_y(width, item.get_uv_size()) * window_size.y;
+                (outline_text, width)
+            });
+            let sized = item.get_uv_size().x > 0.0 || item.get_uv_size().y > 0.0;
+            let is_mesh = !item.get_instances().is_empty() || !item.get_polygon().is_empty();
+            let rect_children = sized && !is_mesh;
+            let item_anchor_vec = item.get_anchor().as_vec();
+
+            let shadow =
+                shadow_material
+                    .filter(|_| rect_children)
+                    .map(|(shadow_material, grow)| {
+                        let offset = item.style.shadow.as_ref().map_or(Vec2::ZERO, |s| s.offset);
+                        let translation = -item_anchor_vec * size + offset * vec2(1.0, -1.0);
+                        (
+                            cached_materials.get(Some(shadow_material), &mut materials),
+                            // Just behind the item, but in front of anything behind it
+                            Transform::from_translation(
+                                translation.extend(-MINOR_DEPTH_AUTO_STEP)
+                                    + item.style.render_transform.translation,
+                            )
+                            .with_scale(
+                                (size + 2.0 * grow).max(Vec2::ZERO).extend(1.0)
+                                    * item.style.render_transform.scale,
+                            )
+                            .with_rotation(item.style.render_transform.rotation),
+                        )
+                    });
+
+            let has_background = item.style.background_color.a() > 0.0
+                || (item.style.border_color.a() > 0.0 && has_border)
+                || item.style.background_gradient.0.a() > 0.0
+                || item.style.background_gradient.1.a() > 0.0
+                || item.style.gradient_stops.iter().any(|(_, c)| c.a() > 0.0)
+                || item.style.inner_shadow.is_some()
+                || (item.style.backdrop_blur.is_some() && pico.backdrop.is_some())
+                || item.style.material.is_some()
+                || item.style.image.is_some();
+            let background = (rect_children && has_background).then(|| {
+                // Grow the mesh for borders outside the rect
+                let border_offset = material
+                    .as_ref()
+                    .map_or(0.0, |m| m.material_settings.border_offset);
+                let anchor_trans = (-item_anchor_vec * size).extend(0.0);
+                (
+                    cached_materials.get(material, &mut materials),
+                    Transform::from_translation(
+                        anchor_trans + item.style.render_transform.translation,
+                    )
+                    .with_scale(
+                        (size + 2.0 * border_offset).extend(1.0)
+                            * item.style.render_transform.scale,
+                    )
+                    .with_rotation(item.style.render_transform.rotation),
+                )
+            });
+
+            // Text can't be partially clipped
+            let text_child = (rect_children && item.has_text() && item.bbox_in_clip()).then(|| {
+                let mut text_anchor = item.style.anchor_text;
+                let mut text_offset = size * -(item_anchor_vec - text_anchor.as_vec());
+                if let Some(baseline_px) = baseline_px {
+                    // Place the top of the first line one ascent above the baseline
+                    if let Some(font) = fonts.get(&item.style.font) {
+                        let ascent = font.font.as_scaled(font_size).ascent();
+                        let top = size.y * (0.5 - item_anchor_vec.y);
+                        text_anchor = Anchor::Custom(vec2(text_anchor.as_vec().x, 0.5));
+                        text_offset.y = top - baseline_px + ascent;
+                    }
+                }
+
+                if item.style.crisp_text {
+                    // Snap to the physical pixel grid so glyphs aren't sampled between pixels. The
+                    // origin is the window center, which is between pixels if the size is odd
+                    let world = item_pos.xy() + text_offset;
+                    let half_window = (window_size * scale_factor).round() * 0.5;
+                    let snapped =
+                        ((world * scale_factor + half_window).round() - half_window) / scale_factor;
+                    text_offset += snapped - world;
+                }
+                (
+                    text_anchor,
+                    Transform::from_translation(
+                        text_offset.extend(0.0001) + item.style.render_transform.translation,
+                    )
+                    .with_scale(item.style.render_transform.scale)
+                    .with_rotation(item.style.render_transform.rotation),
+                )
+            });
+
+            // If only the window size changed, update the existing entities in place instead of
+            // respawning them, which stutters while the window is being resized
+            if !generate && rect_children && outline.is_none() {
+                let existing = pico
+                    .state
+                    .get(&spatial_id)
+                    .and_then(|state_item| state_item.entity)
+                    .and_then(|entity| pico_entites.get_mut(entity).ok());
+                if let Some((_, _, mut pico_entity)) = existing {
+                    if pico_entity.shadow.is_some() == shadow.is_some()
+                        && pico_entity.background.is_some() == background.is_some()
+                        && pico_entity.text.is_some() == text_child.is_some()
+                    {
+                        pico_entity.size = size;
+                        pico_entity.hit_padding = hit_padding;
+                        if let (Some(entity), Some(shadow)) = (pico_entity.shadow, shadow) {
+                            commands.entity(entity).insert(shadow);
+                        }
+                        if let (Some(entity), Some(background)) =
+                            (pico_entity.background, background)
+                        {
+                            commands.entity(entity).insert(background);
+                        }
+                        if let (Some(entity), Some((text_anchor, transform))) =
+                            (pico_entity.text, text_child)
+                        {
+                            commands.entity(entity).insert((
+                                text,
+                                text_anchor,
+                                transform,
+                                Text2dBounds { size },
+                            ));
+                        }
+                        continue;
+                    }
+                }
+            }
 
             let state_item = if let Some(old_state_item) = pico.state.get_mut(&spatial_id) {
-                let entity = old_state_item.entity.unwrap();
-                if pico_entites.get(entity).is_ok() {
-                    commands.entity(entity).despawn_recursive();
+                if let Some(entity) = old_state_item.entity {
+                    if pico_entites.get(entity).is_ok() {
+                        commands.entity(entity).despawn_recursive();
+                    }
                 }
                 old_state_item
             } else {
                 pico.state.insert(spatial_id, StateItem::default());
                 pico.state.get_mut(&spatial_id).unwrap()
             };
-            let text = Text {
-                sections: vec![TextSection::new(
-                    item.text.clone(),
-                    TextStyle {
-                        font_size,
-                        color: item.style.text_color,
-                        font: item.style.font.clone(),
-                    },
-                )],
-                justify: item.style.justify,
-                linebreak_behavior: BreakLineOn::WordBoundary,
-            };
             state_item.life = item.get_life();
             state_item.id = item.id.unwrap();
-            if item.get_uv_size().x > 0.0 || item.get_uv_size().y > 0.0 {
-                let trans = Transform::from_translation(*item_pos);
-                let mut entity = commands.spawn(PicoEntity {
+            let item_scale = distance_scale(item);
+            if sized {
+                let trans =
+                    Transform::from_translation(*item_pos).with_scale(Vec3::splat(item_scale));
+                let mut pico_entity = PicoEntity {
                     spatial_id,
                     anchor: item.get_anchor(),
                     size,
-                });
-
-                entity.insert(SpatialBundle {
-                    transform: trans,
-                    ..default()
-                });
+                    hit_padding,
+                    shadow: None,
+                    background: None,
+                    text: None,
+                };
+                let mut entity = commands.spawn((
+                    PicoOwner::<P>::default(),
+                    SpatialBundle {
+                        transform: trans,
+                        ..default()
+                    },
+                    layers,
+                ));
 
                 entity.with_children(|builder| {
-                    let item_anchor_vec = item.get_anchor().as_vec();
-                    if item.style.background_color.a() > 0.0
-                        || item.style.background_gradient.0.a() > 0.0
-                        || item.style.background_gradient.1.a() > 0.0
-                        || item.style.material.is_some()
-                        || item.style.image.is_some()
-                    {
-                        let material_handle = cached_materials.get(material, &mut materials);
-                        let anchor_trans = (-item_anchor_vec * size).extend(0.0);
-                        let mut entity = builder.spawn(MaterialMesh2dBundle {
-                            mesh: Mesh2dHandle(mesh_handles.rect.clone_weak()),
-                            material: material_handle.clone(),
-                            transform: Transform::from_translation(
-                                anchor_trans + item.style.render_transform.translation,
-                            )
-                            .with_scale(size.extend(1.0) * item.style.render_transform.scale)
-                            .with_rotation(item.style.render_transform.rotation),
-                            ..default()
-                        });
+                    if is_mesh {
+                        let mesh = if item.get_polygon().is_empty() {
+                            instanced_mesh(item, window_size)
+                        } else {
+                            polygon_mesh(item, window_size)
+                        };
+                        builder.spawn((
+                            MaterialMesh2dBundle {
+                                mesh: meshes.add(mesh).into(),
+                                material: mesh_handles.instanced_material.clone_weak(),
+                                ..default()
+                            },
+                            layers,
+                        ));
+                        return;
+                    }
+                    if let Some((material, transform)) = shadow {
+                        let entity = builder.spawn((
+                            MaterialMesh2dBundle {
+                                mesh: mesh_handles.rect.clone_weak().into(),
+                                material,
+                                transform,
+                                ..default()
+                            },
+                            layers,
+                        ));
+                        pico_entity.shadow = Some(entity.id());
+                    }
+                    if let Some((material, transform)) = background {
+                        let mut entity = builder.spawn((
+                            MaterialMesh2dBundle {
+                                mesh: Mesh2dHandle(
+                                    item.style
+                                        .mesh
+                                        .clone()
+                                        .unwrap_or_else(|| mesh_handles.rect.clone_weak()),
+                                ),
+                                material,
+                                transform,
+                                ..default()
+                            },
+                            layers,
+                        ));
                         if let Some(material) = item.style.material {
                             entity.insert(SwapMaterialEntity(material));
                         }
+                        pico_entity.background = Some(entity.id());
+                    }
+                    if let Some((text_anchor, transform)) = text_child {
+                        let entity = builder.spawn((
+                            Text2dBundle {
+                                text,
+                                text_anchor,
+                                transform,
+                                text_2d_bounds: Text2dBounds { size },
+                                ..default()
+                            },
+                            text_spans,
+                            PicoOwner::<P>::default(),
+                            layers,
+                        ));
+                        pico_entity.text = Some(entity.id());
+                        if let Some((outline_text, width)) = &outline {
+                            for direction in TEXT_OUTLINE_DIRECTIONS {
+                                let offset = (direction * *width).extend(TEXT_OUTLINE_Z);
+                                builder.spawn((
+                                    Text2dBundle {
+                                        text: outline_text.clone(),
+                                        text_anchor,
+                                        transform: transform
+                                            .with_translation(transform.translation + offset),
+                                        text_2d_bounds: Text2dBounds { size },
+                                        ..default()
+                                    },
+                                    layers,
+                                ));
+                            }
+                        }
                     }
-
-                    builder.spawn(Text2dBundle {
-                        text,
-                        text_anchor: item.style.anchor_text,
-                        transform: Transform::from_translation(
-                            (size * -(item_anchor_vec - item.style.anchor_text.as_vec()))
-                                .extend(0.0001)
-                                + item.style.render_transform.translation,
-                        )
-                        .with_scale(item.style.render_transform.scale)
-                        .with_rotation(item.style.render_transform.rotation),
-                        text_2d_bounds: Text2dBounds { size },
-                        ..default()
-                    });
                 });
+                entity.insert(pico_entity);
                 state_item.bbox = get_bbox(
-                    item.get_uv_size(),
+                    item.get_uv_size() * item_scale,
                     trans.translation.xy() / window_size * vec2(1.0, -1.0) + 0.5,
                     &item.get_anchor(),
                 );
-                state_item.interactable = true;
+                state_item.interactable = item.interactable;
                 state_item.entity = Some(entity.id());
             } else {
-                let entity = commands
-                    .spawn((
-                        PicoEntity {
-                            spatial_id,
-                            anchor: item.get_anchor(),
-                            size,
-                        },
+                let mut entity = commands.spawn((
+                    PicoEntity {
+                        spatial_id,
+                        anchor: item.get_anchor(),
+                        size,
+                        hit_padding,
+                        shadow: None,
+                        background: None,
+                        text: None,
+                    },
+                    PicoOwner::<P>::default(),
+                    layers,
+                ));
+                if !item.has_text() {
+                    entity.insert(SpatialBundle::from_transform(
+                        Transform::from_translation(*item_pos).with_scale(Vec3::splat(item_scale)),
+                    ));
+                } else {
+                    entity.insert((
                         Text2dBundle {
                             text,
                             text_anchor: item.style.anchor_text,
-                            transform: Transform::from_translation(*item_pos),
+                            transform: Transform::from_translation(*item_pos)
+                                .with_scale(Vec3::splat(item_scale)),
                             ..default()
                         },
-                    ))
-                    .id();
-                state_item.entity = Some(entity);
+                        text_spans,
+                    ));
+                    if let Some((outline_text, width)) = outline {
+                        entity.with_children(|builder| {
+                            for direction in TEXT_OUTLINE_DIRECTIONS {
+                                builder.spawn((
+                                    Text2dBundle {
+                                        text: outline_text.clone(),
+                                        text_anchor: item.style.anchor_text,
+                                        transform: Transform::from_translation(
+                                            (direction * width).extend(TEXT_OUTLINE_Z),
+                                        ),
+                                        ..default()
+                                    },
+                                    layers,
+                                ));
+                            }
+                        });
+                    }
+                }
+                state_item.entity = Some(entity.id());
+            }
+        }
+    }
+
+    // Expose items to screen readers, bevy_winit builds the accessibility tree from these nodes.
+    // Skipped until a screen reader asks for the tree since it is rebuilt for every item each frame.
+    if accessibility_requested.is_some_and(|requested| requested.get()) {
+        for (item, item_pos) in items.iter().zip(item_positions.iter()) {
+            if !item_pos.is_finite() {
+                continue;
+            }
+            let Some(role) = item
+                .role
+                .or_else(|| item.has_text().then_some(Role::StaticText))
+            else {
+                continue;
+            };
+            let Some(entity) = pico
+                .state
+                .get(&item.get_spatial_id())
+                .and_then(|state_item| state_item.entity)
+            else {
+                continue;
+            };
+            let mut node = NodeBuilder::new(role);
+            if item.has_text() {
+                node.set_name(item.get_text());
             }
+            if item.position_3d.is_none() {
+                // Physical pixels of the window
+                let bbox = item.get_bbox()
+                    * window_size.extend(window_size.x).extend(window_size.y)
+                    * scale_factor;
+                node.set_bounds(accesskit::Rect {
+                    x0: bbox.x as f64,
+                    y0: bbox.y as f64,
+                    x1: bbox.z as f64,
+                    y1: bbox.w as f64,
+                });
+            }
+            commands.entity(entity).insert(AccessibilityNode(node));
         }
     }
 
     for (_, state_item) in pico.state.iter_mut() {
-        let entity = state_item.entity.unwrap();
-        // Remove that are no longer in use
-        if state_item.life < 0.0 && pico_entites.get(entity).is_ok() {
+        let Some(entity) = state_item.entity else {
+            continue;
+        };
+        // Remove that are no longer in use. Between throttled rebuilds they stay displayed until
+        // what replaced them is spawned, see `Pico::update_interval`
+        if state_item.life < 0.0 && rebuild && pico_entites.get(entity).is_ok() {
             commands.entity(entity).despawn_recursive();
         }
     }
@@ -283,14 +781,309 @@ pub fn render(
         }
     }
 
+    // Keep the allocations for next frame
+    items.clear();
+    pico.items = items;
+    scratch.item_positions = item_positions;
+    cached_materials.remove_unused();
+    scratch.cached_materials = cached_materials;
+
     // clean up state
-    pico.state.retain(|_, state_item| state_item.life >= 0.0);
+    pico.state.retain(|_, state_item| {
+        state_item.life >= 0.0 || (!rebuild && state_item.entity.is_some())
+    });
     pico.interacting = interacting;
+    pico.dragging = currently_dragging;
     pico.window_size = window_size;
     pico.mouse_button_input = Some(mouse_button_input.clone());
+    pico.keyboard_input = Some(keyboard_input.clone());
+    pico.scroll_delta = mouse_wheel_events
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => vec2(event.x, event.y),
+            MouseScrollUnit::Pixel => vec2(event.x, event.y) / 10.0, //TODO: idk about scale
+        })
+        .sum();
+    // Innermost scroll areas get the wheel first, the rest goes to the areas around them
+    let mut scroll_areas = std::mem::take(&mut pico.scroll_areas);
+    scroll_areas.sort_by(|a, b| b.depth.total_cmp(&a.depth));
+    let mut remaining = pico.scroll_delta;
+    pico.routed_scroll_delta.clear();
+    for area in &scroll_areas {
+        let hovered = pico
+            .state
+            .get(&area.spatial_id)
+            .is_some_and(|state_item| state_item.hover);
+        let delta = if hovered {
+            vec2(
+                remaining.x,
+                remaining.y.clamp(-area.room_down, area.room_up),
+            )
+        } else {
+            Vec2::ZERO
+        };
+        remaining -= delta;
+        pico.routed_scroll_delta.insert(area.spatial_id, delta);
+    }
+    scroll_areas.clear();
+    pico.scroll_areas = scroll_areas;
+    pico.cursor_uv_position = window.cursor_position().map(|p| p / window_size);
+    pico.scale_factor = scale_factor;
+    pico.viewport = camera.viewport.as_ref().map(|viewport| {
+        Rect::from_corners(
+            viewport.physical_position.as_vec2() / scale_factor,
+            (viewport.physical_position + viewport.physical_size).as_vec2() / scale_factor,
+        )
+    });
     pico.internal_auto_depth = 0.5;
+    pico.delta_seconds = time.delta_seconds();
+    // Keep the font data for Pico::measure_text()
+    pico.fonts.retain(|id, _| fonts.contains(*id));
+    for (id, font) in fonts.iter() {
+        pico.fonts.entry(id).or_insert_with(|| font.font.clone());
+    }
+    pico.add_retained();
+    pico.add_focus_outline();
+    pico.add_tooltip();
+}
+
+/// Copies the `PicoTheme` resource to `Pico::theme` when it is inserted or changed
+pub fn apply_theme<P: PicoResource>(theme: Option<Res<PicoTheme>>, mut pico_resource: ResMut<P>) {
+    if let Some(theme) = theme.filter(|theme| theme.is_changed()) {
+        pico_resource.pico_mut().theme = theme.clone();
+    }
+}
+
+/// Finds the window uv bounds of each text span for `Pico::text_links()` once the text is laid out.
+/// Only items that `text_links()` was used on are updated.
+pub fn update_text_spans<P: PicoResource>(
+    camera: Query<&Camera, With<P::Camera>>,
+    windows: Query<(&Window, Has<PrimaryWindow>)>,
+    mut pico_resource: ResMut<P>,
+    text_query: Query<(&TextSpans, &TextLayoutInfo, &Anchor, &GlobalTransform), With<PicoOwner<P>>>,
+) {
+    let Some(window) = camera
+        .get_single()
+        .ok()
+        .and_then(|camera| camera_window(camera, &windows))
+    else {
+        return;
+    };
+    let pico = pico_resource.pico_mut();
+    let window_size = Vec2::new(window.width(), window.height());
+    let scale_factor = window.scale_factor();
+    for (spans, layout, anchor, transform) in &text_query {
+        let Some(state_item) = pico
+            .state
+            .get_mut(&spans.spatial_id)
+            .filter(|state_item| state_item.track_spans)
+        else {
+            continue;
+        };
+        let span_count = spans.sections.iter().max().map_or(0, |span| span + 1);
+        let mut bboxes = vec![vec4(f32::MAX, f32::MAX, f32::MIN, f32::MIN); span_count];
+        // Placed like bevy's text2d extraction, glyph positions are physical pixels with +y up
+        let origin = layout.logical_size * -(anchor.as_vec() + 0.5);
+        for glyph in &layout.glyphs {
+            let Some(bbox) = spans
+                .sections
+                .get(glyph.section_index)
+                .and_then(|span| bboxes.get_mut(*span))
+            else {
+                continue;
+            };
+            let half_size = glyph.size * 0.5;
+            for corner in [
+                vec2(-1.0, -1.0),
+                vec2(1.0, -1.0),
+                vec2(-1.0, 1.0),
+                vec2(1.0, 1.0),
+            ] {
+                let local = origin + (glyph.position + corner * half_size) / scale_factor;
+                let world = transform.transform_point(local.extend(0.0));
+                let uv = world.xy() / window_size * vec2(1.0, -1.0) + 0.5;
+                *bbox = vec4(
+                    bbox.x.min(uv.x),
+                    bbox.y.min(uv.y),
+                    bbox.z.max(uv.x),
+                    bbox.w.max(uv.y),
+                );
+            }
+        }
+        for bbox in &mut bboxes {
+            // Spans without any glyphs, like whitespace
+            if bbox.x > bbox.z {
+                *bbox = Vec4::ZERO;
+            }
+        }
+        state_item.span_bboxes = bboxes;
+    }
 }
 
+/// The window `camera` renders to, None if it renders to an image
+fn camera_window<'a>(
+    camera: &Camera,
+    windows: &'a Query<(&Window, Has<PrimaryWindow>)>,
+) -> Option<&'a Window> {
+    match camera.target {
+        RenderTarget::Window(WindowRef::Primary) => windows
+            .iter()
+            .find_map(|(window, primary)| primary.then_some(window)),
+        RenderTarget::Window(WindowRef::Entity(entity)) => {
+            windows.get(entity).ok().map(|(window, _)| window)
+        }
+        _ => None,
+    }
+}
+
+/// The ndc of `position`, None if it is behind the camera
+fn position_3d_ndc(world_to_ndc: Mat4, position: Vec3) -> Option<Vec3> {
+    let ndc = world_to_ndc.project_point3(position);
+    // With reversed z, points behind the camera have a negative ndc z
+    (ndc.is_finite() && ndc.z > 0.0).then_some(ndc)
+}
+
+/// Orders depths front to back. total_cmp so a NaN depth can't panic, NaN items still get a
+/// deterministic order: positive NaN first and negative NaN last.
+fn front_to_back(a: f32, b: f32) -> std::cmp::Ordering {
+    b.total_cmp(&a)
+}
+
+/// Splits emoji into their own sections when `emoji_font` is set so they use that font and are not
+/// tinted by `text_color`. Otherwise each of `sections`, or the text, is a single section.
+/// Also returns the index into `sections` each text section came from.
+fn text_sections(item: &ProcessedPicoItem, font_size: f32) -> (Vec<TextSection>, Vec<usize>) {
+    let default_runs = [(item.text.clone(), item.style.text_color)];
+    let runs = if item.sections.is_empty() {
+        &default_runs[..]
+    } else {
+        &item.sections[..]
+    };
+    let mut sections: Vec<TextSection> = Vec::new();
+    let mut spans = Vec::new();
+    for (span, (text, color)) in runs.iter().enumerate() {
+        let text_style = TextStyle {
+            font_size,
+            color: color.with_a(color.a() * item.get_opacity()),
+            font: item.style.font.clone(),
+        };
+        let Some(emoji_font) = &item.style.emoji_font else {
+            sections.push(TextSection::new(text.clone(), text_style));
+            spans.push(span);
+            continue;
+        };
+        let emoji_style = TextStyle {
+            font_size,
+            color: Color::WHITE.with_a(item.get_opacity()),
+            font: emoji_font.clone(),
+        };
+        let mut last_is_emoji = None;
+        for c in text.chars() {
+            // Joiners and variation selectors stay with the run they are in
+            let is_emoji = if matches!(c, '\u{200D}' | '\u{FE0F}') {
+                last_is_emoji.unwrap_or(false)
+            } else {
+                is_emoji(c)
+            };
+            if last_is_emoji == Some(is_emoji) {
+                sections.last_mut().unwrap().value.push(c);
+            } else {
+                let style = if is_emoji { &emoji_style } else { &text_style };
+                sections.push(TextSection::new(c.to_string(), style.clone()));
+                spans.push(span);
+                last_is_emoji = Some(is_emoji);
+            }
+        }
+    }
+    (sections, spans)
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(c as u32,
+        0x1F000..=0x1FAFF // Pictographs, emoticons, transport, flags, etc...
+        | 0x2600..=0x27BF // Misc symbols and dingbats
+        | 0x231A | 0x231B | 0x23E9..=0x23FA // Watch, hourglass, media controls
+        | 0x2B50 | 0x2B55 // Star, circle
+    )
+}
+
+/// Builds one mesh with a quad per instance. Positions are in pixels relative to the item position.
+fn instanced_mesh(item: &ProcessedPicoItem, window_size: Vec2) -> Mesh {
+    let origin = item.get_uv_position();
+    let instances = item.get_instances();
+    let mut positions = Vec::with_capacity(instances.len() * 4);
+    let mut uvs = Vec::with_capacity(instances.len() * 4);
+    let mut colors = Vec::with_capacity(instances.len() * 4);
+    let mut indices = Vec::with_capacity(instances.len() * 6);
+    for (bbox, color) in instances {
+        let i = positions.len() as u32;
+        let a = (bbox.xy() - origin) * window_size * vec2(1.0, -1.0);
+        let b = (bbox.zw() - origin) * window_size * vec2(1.0, -1.0);
+        positions.extend([
+            [a.x, a.y, 0.0],
+            [b.x, a.y, 0.0],
+            [b.x, b.y, 0.0],
+            [a.x, b.y, 0.0],
+        ]);
+        uvs.extend([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        let color = color.with_a(color.a() * item.get_opacity());
+        colors.extend([color.as_linear_rgba_f32(); 4]);
+        indices.extend([i, i + 1, i + 2, i, i + 2, i + 3]);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Fan triangulation of the convex polygon. Positions are in pixels relative to the item position.
+fn polygon_mesh(item: &ProcessedPicoItem, window_size: Vec2) -> Mesh {
+    let origin = item.get_uv_position();
+    let polygon = item.get_polygon();
+    let positions: Vec<[f32; 3]> = polygon
+        .iter()
+        .map(|p| {
+            ((*p - origin) * window_size * vec2(1.0, -1.0))
+                .extend(0.0)
+                .into()
+        })
+        .collect();
+    let size = item.get_uv_size().max(Vec2::splat(f32::EPSILON));
+    let uvs: Vec<[f32; 2]> = polygon
+        .iter()
+        .map(|p| ((*p - origin) / size).into())
+        .collect();
+    let color = item.style.background_color;
+    let color = color.with_a(color.a() * item.get_opacity());
+    let colors = vec![color.as_linear_rgba_f32(); polygon.len()];
+    let mut indices = Vec::with_capacity(polygon.len().saturating_sub(2) * 3);
+    for i in 1..polygon.len().saturating_sub(1) as u32 {
+        indices.extend([0, i, i + 1]);
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Buffers used by `render` that are cleared and reused each frame instead of reallocated
+#[derive(Default)]
+pub struct RenderScratch {
+    item_positions: Vec<Vec3>,
+    /// Kept across frames so unchanged items reuse their material assets
+    cached_materials: MaterialCache,
+}
+
+/// Material handles by material hash. Materials are only added on a cache miss and removed once no
+/// entity uses them anymore.
 #[derive(Default)]
 struct MaterialCache(HashMap<u64, Handle<RectangleMaterial>>);
 
@@ -314,4 +1107,121 @@ impl MaterialCache {
         };
         material_handle
     }
+
+    /// Drop handles that are only held by the cache, so the material asset can be freed. Handles of
+    /// entities that were just despawned are still held by the pending commands and are dropped next
+    /// frame.
+    fn remove_unused(&mut self) {
+        self.0.retain(|_, handle| match handle {
+            Handle::Strong(handle) => Arc::strong_count(handle) > 1,
+            Handle::Weak(_) => false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pico::{Pico, Pico2dCamera, PicoItem},
+        PicoPlugin,
+    };
+    use bevy::{
+        input::InputPlugin,
+        render::{
+            camera::{camera_system, CameraProjection, ManualTextureViews},
+            render_resource::Shader,
+        },
+        window::{WindowCreated, WindowResized, WindowScaleFactorChanged},
+    };
+
+    #[test]
+    fn depth_sort_with_nan() {
+        let mut depths = [0.1, f32::NAN, 0.9, -f32::NAN, 0.5];
+        depths.sort_by(|a, b| front_to_back(*a, *b));
+        assert!(depths[0].is_nan() && depths[0].is_sign_positive());
+        assert_eq!(depths[1..4], [0.9, 0.5, 0.1]);
+        assert!(depths[4].is_nan() && depths[4].is_sign_negative());
+    }
+
+    #[test]
+    fn position_3d_behind_camera() {
+        let projection = PerspectiveProjection::default().get_projection_matrix();
+        let position = Vec3::new(0.0, 0.0, -5.0);
+        let facing = Transform::default().compute_matrix();
+        assert!(position_3d_ndc(projection * facing.inverse(), position).is_some());
+        let looking_away = Transform::default()
+            .looking_to(Vec3::Z, Vec3::Y)
+            .compute_matrix();
+        assert!(position_3d_ndc(projection * looking_away.inverse(), position).is_none());
+    }
+
+    /// Renders a `position_3d` item in front of the origin with the camera at the origin, returns
+    /// how many `PicoEntity` were spawned and, if the item has state, whether it has an entity and
+    /// is hovered
+    fn render_position_3d(camera_transform: Transform) -> (usize, Option<(bool, bool)>) {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+            InputPlugin,
+        ))
+        // Normally added by the window, render, and sprite plugins
+        .add_event::<WindowResized>()
+        .add_event::<WindowCreated>()
+        .add_event::<WindowScaleFactorChanged>()
+        .init_resource::<ManualTextureViews>()
+        .init_asset::<Shader>()
+        .init_asset::<Mesh>()
+        .init_asset::<Image>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<Font>()
+        .add_plugins(PicoPlugin::default())
+        .add_systems(First, camera_system::<Projection>);
+        let mut window = Window::default();
+        window.set_cursor_position(Some(vec2(window.width(), window.height()) * 0.5));
+        app.world.spawn((window, PrimaryWindow));
+        app.world.spawn((
+            Camera::default(),
+            Projection::default(),
+            TransformBundle::from_transform(camera_transform),
+            Pico2dCamera,
+        ));
+        app.world
+            .resource_mut::<ButtonInput<MouseButton>>()
+            .press(MouseButton::Left);
+
+        let mut spatial_id = 0;
+        // Spawned the first frame, its bbox is found the next and it's hovered after that
+        for _ in 0..3 {
+            let mut pico = app.world.resource_mut::<Pico>();
+            let item = pico.add(PicoItem {
+                width: Val::Px(100.0),
+                height: Val::Px(100.0),
+                ..PicoItem::new3d(Vec3::new(0.0, 0.0, -5.0), "3d")
+            });
+            spatial_id = pico.get(&item).get_spatial_id();
+            app.update();
+        }
+        let entities = app.world.query::<&PicoEntity>().iter(&app.world).count();
+        let state_item = app.world.resource::<Pico>().state.get(&spatial_id);
+        (
+            entities,
+            state_item.map(|state_item| (state_item.entity.is_some(), state_item.hover)),
+        )
+    }
+
+    #[test]
+    fn render_position_3d_behind_camera() {
+        let (entities, state_item) = render_position_3d(Transform::default());
+        assert_eq!(entities, 1);
+        assert_eq!(state_item, Some((true, true)));
+
+        let looking_away = Transform::default().looking_to(Vec3::Z, Vec3::Y);
+        let (entities, state_item) = render_position_3d(looking_away);
+        assert_eq!(entities, 0);
+        assert_eq!(state_item, None);
+    }
 }
@@ -1,27 +1,86 @@
-use bevy::{
-    input::mouse::{MouseScrollUnit, MouseWheel},
-    math::vec2,
-    prelude::*,
-    sprite::Anchor,
+use bevy::{a11y::accesskit::Role, math::vec2, prelude::*, sprite::Anchor};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    f32::consts::PI,
+    hash::{Hash, Hasher},
+    ops::RangeInclusive,
+    path::PathBuf,
 };
 
 use crate::{
-    pico::{ItemIndex, ItemStyle, PicoItem},
+    guard::Guard,
+    pico::{lerp, Easing, ItemIndex, ItemStyle, PicoItem, PopupSide},
     Pico,
 };
 
+/// A manual spatial id for a part of a widget, derived from the widget's `id` and a `part` name so it
+/// can't collide with ids the user picks for other widgets.
+pub fn child_id(id: u64, part: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (id, part).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Key of a widget's state in `Pico::persistent`, see `Pico::set_persistent()`
+fn persistent_key(widget: &str, id: u64) -> String {
+    format!("{widget}/{id}")
+}
+
 // -------------------------
 // Button example widget
 // -------------------------
 
+/// Interaction state passed to widget style closures
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WidgetInteraction {
+    pub hovered: bool,
+    /// The mouse was pressed on the item and is still held
+    pub pressed: bool,
+    /// Only set by toggle widgets
+    pub toggled: bool,
+}
+
+impl WidgetInteraction {
+    pub fn of(pico: &Pico, index: &ItemIndex) -> Self {
+        WidgetInteraction {
+            hovered: pico.hovered(index),
+            pressed: pico
+                .get_state(index)
+                .is_some_and(|state| state.drag.is_some()),
+            toggled: false,
+        }
+    }
+}
+
 pub fn button(pico: &mut Pico, item: PicoItem) -> ItemIndex {
-    let index = pico.add(item);
-    let c = pico.get(&index).style.background_color;
-    pico.get_mut(&index).style.background_color = if pico.hovered(&index) {
-        c + Color::rgba(0.06, 0.06, 0.06, 0.0)
-    } else {
-        c
-    };
+    button_styled(pico, item, default_button_style)
+}
+
+/// Lightens the background while hovered
+pub fn default_button_style(base: &ItemStyle, interaction: WidgetInteraction) -> ItemStyle {
+    let mut style = base.clone();
+    if interaction.hovered {
+        style.background_color = base.background_color + Color::rgba(0.06, 0.06, 0.06, 0.0);
+    }
+    style
+}
+
+/// A button with its style for the current interaction state computed by `style`
+/// from the item's style and the interaction.
+pub fn button_styled(
+    pico: &mut Pico,
+    item: PicoItem,
+    style: impl Fn(&ItemStyle, WidgetInteraction) -> ItemStyle,
+) -> ItemIndex {
+    let index = pico.add(PicoItem {
+        focusable: true,
+        role: item.role.or(Some(Role::Button)),
+        ..item
+    });
+    let interaction = WidgetInteraction::of(pico, &index);
+    let item = pico.get_mut(&index);
+    item.style = style(&item.style, interaction);
     index
 }
 
@@ -35,47 +94,1889 @@ pub fn toggle_button(
     enabled_bg: Color,
     toggle_state: &mut bool,
 ) -> ItemIndex {
-    let index = pico.add(item);
-    let mut c = pico.get(&index).style.background_color;
+    toggle_button_styled(pico, item, toggle_state, |base, interaction| {
+        default_toggle_button_style(base, interaction, enabled_bg)
+    })
+}
+
+/// Uses `enabled_bg` while toggled and lightens the background while hovered
+pub fn default_toggle_button_style(
+    base: &ItemStyle,
+    interaction: WidgetInteraction,
+    enabled_bg: Color,
+) -> ItemStyle {
+    let mut style = base.clone();
+    if interaction.toggled {
+        style.background_color = enabled_bg;
+    }
+    if interaction.hovered {
+        style.background_color = style.background_color + Color::rgb(0.08, 0.08, 0.08);
+    }
+    style
+}
+
+/// A toggle button with its style for the current interaction state computed by `style`.
+/// `WidgetInteraction::toggled` is the state after this frame's click.
+pub fn toggle_button_styled(
+    pico: &mut Pico,
+    item: PicoItem,
+    toggle_state: &mut bool,
+    style: impl Fn(&ItemStyle, WidgetInteraction) -> ItemStyle,
+) -> ItemIndex {
+    let index = pico.add(PicoItem {
+        focusable: true,
+        role: item.role.or(Some(Role::ToggleButton)),
+        ..item
+    });
     if pico.clicked(&index) {
         *toggle_state = !*toggle_state;
     }
-    if *toggle_state {
-        c = enabled_bg;
+    let interaction = WidgetInteraction {
+        toggled: *toggle_state,
+        ..WidgetInteraction::of(pico, &index)
+    };
+    let item = pico.get_mut(&index);
+    item.style = style(&item.style, interaction);
+    index
+}
+
+// -------------------------
+// Toggle switch example widget
+// -------------------------
+
+pub const SWITCH_OFF_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+pub const SWITCH_ON_COLOR: Color = Color::rgb(0.2, 0.55, 0.3);
+/// Fraction of the knob travel per second
+pub const SWITCH_SPEED: f32 = 6.0;
+
+/// Pill shaped switch filling `parent`. `id` must be consistent across frames so the knob animation
+/// state persists. Returns the track index.
+pub fn switch(pico: &mut Pico, parent: ItemIndex, on: &mut bool, id: u64) -> ItemIndex {
+    let key = persistent_key("switch", id);
+    let track = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        style: ItemStyle {
+            corner_radius: Val::Percent(50.0),
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        parent: Some(parent),
+        spatial_id: Some(id), // Manually set id
+        focusable: true,
+        role: Some(Role::Switch),
+        ..default()
+    });
+
+    // Restore the stored state until the switch has its own state
+    if pico.get_state(&track).is_none() {
+        if let Some(stored) = pico.get_persistent(&key) {
+            *on = stored;
+        }
+    }
+
+    // Step the knob position toward the current state
+    let target = if *on { 1.0 } else { 0.0 };
+    let t = pico.animate(id, target, SWITCH_SPEED, Easing::Smoothstep);
+
+    let bbox = pico.get(&track).get_bbox();
+    let track_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+    let knob_px = track_px.y * 0.8;
+    let margin_px = track_px.y * 0.1;
+    let knob = pico.add(PicoItem {
+        x: Val::Px(lerp(margin_px, track_px.x - margin_px - knob_px, t)),
+        width: Val::Px(knob_px),
+        height: Val::Px(knob_px),
+        style: ItemStyle {
+            corner_radius: Val::Percent(50.0),
+            background_color: Color::rgb(0.9, 0.9, 0.9),
+            ..default()
+        },
+        anchor: Anchor::CenterLeft,
+        anchor_parent: Anchor::CenterLeft,
+        parent: Some(track),
+        ..default()
+    });
+
+    // The knob is in front of the track so it also needs to be checked for clicks
+    if pico.clicked(&track) || pico.clicked(&knob) {
+        *on = !*on;
+        pico.set_persistent(&key, on);
+    }
+
+    let c = Color::rgba_from_array(
+        Vec4::from(SWITCH_OFF_COLOR.as_rgba_f32())
+            .lerp(Vec4::from(SWITCH_ON_COLOR.as_rgba_f32()), t),
+    );
+    pico.get_mut(&track).style.background_color = if pico.hovered(&track) || pico.hovered(&knob) {
+        c + Color::rgba(0.08, 0.08, 0.08, 0.0)
+    } else {
+        c
+    };
+    track
+}
+
+// -------------------------
+// Radio group example widget
+// -------------------------
+
+/// One row per option stacked in `parent`, clicking a row selects it.
+/// Returns the row index of each option.
+pub fn radio_group(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    options: &[&str],
+    selected: &mut usize,
+) -> Vec<ItemIndex> {
+    let mut rows = Vec::new();
+    let _guard = pico.vstack(Val::Px(0.0), Val::Px(0.0), false, &parent);
+    for (i, option) in options.iter().enumerate() {
+        let row = pico.add(PicoItem {
+            // Leave room for the circle before the text
+            text: format!("      {}", option),
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0 / options.len() as f32),
+            style: ItemStyle {
+                anchor_text: Anchor::CenterLeft,
+                justify: JustifyText::Left,
+                ..default()
+            },
+            anchor: Anchor::TopLeft,
+            parent: Some(parent),
+            focusable: true,
+            ..default()
+        });
+        let bbox = pico.get(&row).get_bbox();
+        let row_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+        let circle_px = row_px.y * 0.6;
+
+        let _guard = pico.stack_bypass();
+        let circle = pico.add(PicoItem {
+            x: Val::Px(row_px.y * 0.2),
+            width: Val::Px(circle_px),
+            height: Val::Px(circle_px),
+            style: ItemStyle {
+                corner_radius: Val::Percent(50.0),
+                background_color: Color::rgb(0.2, 0.2, 0.2),
+                border_width: Val::Px(1.0),
+                border_color: Color::rgb(0.5, 0.5, 0.5),
+                ..default()
+            },
+            anchor: Anchor::CenterLeft,
+            anchor_parent: Anchor::CenterLeft,
+            parent: Some(row),
+            ..default()
+        });
+        if pico.clicked(&row) || pico.clicked(&circle) {
+            *selected = i;
+        }
+        // Same hover highlight as button, the row itself has no background to highlight
+        let interaction = WidgetInteraction {
+            hovered: pico.hovered(&row) || pico.hovered(&circle),
+            ..default()
+        };
+        let circle_item = pico.get_mut(&circle);
+        circle_item.style = default_button_style(&circle_item.style, interaction);
+        if *selected == i {
+            pico.add(PicoItem {
+                width: Val::Percent(50.0),
+                height: Val::Percent(50.0),
+                style: ItemStyle {
+                    corner_radius: Val::Percent(50.0),
+                    background_color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+                anchor: Anchor::Center,
+                anchor_parent: Anchor::Center,
+                parent: Some(circle),
+                interactable: false,
+                ..default()
+            });
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+// -------------------------
+// Label example widget
+// -------------------------
+
+/// WCAG relative luminance
+pub fn relative_luminance(color: Color) -> f32 {
+    let c = color.as_linear_rgba_f32();
+    0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2]
+}
+
+/// Black or white, whichever has the higher WCAG contrast ratio against `bg`.
+pub fn contrast_text_color(bg: Color) -> Color {
+    let l = relative_luminance(bg);
+    let contrast_black = (l + 0.05) / 0.05;
+    let contrast_white = 1.05 / (l + 0.05);
+    if contrast_black > contrast_white {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
+
+/// Fills `parent` with `bg`, the text color is chosen automatically for readability.
+/// Override with `pico.get_mut(&index).style.text_color` if needed.
+pub fn label(pico: &mut Pico, parent: ItemIndex, text: &str, bg: Color) -> ItemIndex {
+    pico.add(PicoItem {
+        text: text.to_string(),
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        style: ItemStyle {
+            background_color: bg,
+            text_color: contrast_text_color(bg),
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        parent: Some(parent),
+        role: Some(Role::StaticText),
+        ..default()
+    })
+}
+
+// -------------------------
+// Drag grip example widget
+// -------------------------
+
+/// Decorative grip dots centered in `parent` to indicate it can be dragged.
+/// The dots are not interactable so dragging them drags the parent.
+/// If `vertical` the dots are arranged in columns running top to bottom.
+pub fn drag_grip(pico: &mut Pico, parent: ItemIndex, vertical: bool) -> ItemIndex {
+    let (cols, rows) = if vertical { (2, 3) } else { (3, 2) };
+    let bbox = pico.get(&parent).get_bbox();
+    let parent_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+    let dot_px = parent_px.x.min(parent_px.y) * 0.12;
+    let spacing_px = dot_px * 2.0;
+    let grip = pico.add(PicoItem {
+        width: Val::Px(cols as f32 * spacing_px),
+        height: Val::Px(rows as f32 * spacing_px),
+        anchor: Anchor::Center,
+        anchor_parent: Anchor::Center,
+        parent: Some(parent),
+        interactable: false,
+        ..default()
+    });
+    for col in 0..cols {
+        for row in 0..rows {
+            pico.add(PicoItem {
+                x: Val::Px((col as f32 + 0.5) * spacing_px),
+                y: Val::Px((row as f32 + 0.5) * spacing_px),
+                width: Val::Px(dot_px),
+                height: Val::Px(dot_px),
+                style: ItemStyle {
+                    corner_radius: Val::Percent(50.0),
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.4),
+                    ..default()
+                },
+                anchor: Anchor::Center,
+                parent: Some(grip),
+                interactable: false,
+                ..default()
+            });
+        }
+    }
+    grip
+}
+
+// -------------------------
+// Slider example widget
+// -------------------------
+
+/// Fraction of the slider range the value changes by for each line of mouse wheel movement
+pub const SLIDER_SCROLL_STEP: f32 = 0.05;
+
+/// Dragging anywhere on the track sets the value from the cursor position. The mouse wheel
+/// changes the value by `SLIDER_SCROLL_STEP` of the range while hovered.
+pub fn slider(pico: &mut Pico, item: PicoItem, value: &mut f32, min: f32, max: f32) -> ItemIndex {
+    let track = pico.add(PicoItem {
+        focusable: true,
+        ..item
+    });
+    let bbox = pico.get(&track).get_bbox();
+    let mut dragging = false;
+    if let Some(state) = pico.get_state(&track) {
+        if state.drag.is_some() {
+            if let Some(cursor) = pico.cursor_uv_position {
+                let width = bbox.z - bbox.x;
+                let t = if width > 0.0 {
+                    ((cursor.x - bbox.x) / width).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                *value = lerp(min, max, t);
+            }
+            dragging = true;
+        }
+    }
+    let scroll = pico.scroll_delta(&track).y;
+    if scroll != 0.0 && !dragging {
+        *value =
+            (*value + scroll * SLIDER_SCROLL_STEP * (max - min)).clamp(min.min(max), min.max(max));
+    }
+    let t = if max != min {
+        ((*value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let track_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+
+    let _guard = pico.stack_bypass();
+    pico.add(PicoItem {
+        width: Val::Percent(t * 100.0),
+        height: Val::Percent(100.0),
+        style: ItemStyle {
+            corner_radius: pico.get(&track).style.corner_radius,
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.2),
+            ..default()
+        },
+        anchor: Anchor::CenterLeft,
+        anchor_parent: Anchor::CenterLeft,
+        parent: Some(track),
+        interactable: false,
+        ..default()
+    });
+    let handle_px = track_px.y * 2.0;
+    pico.add(PicoItem {
+        x: Val::Px(t * track_px.x),
+        width: Val::Px(handle_px),
+        height: Val::Px(handle_px),
+        style: ItemStyle {
+            corner_radius: Val::Percent(50.0),
+            background_color: Color::rgb(0.9, 0.9, 0.9),
+            ..default()
+        },
+        anchor: Anchor::Center,
+        anchor_parent: Anchor::CenterLeft,
+        parent: Some(track),
+        interactable: false,
+        ..default()
+    });
+
+    let c = pico.get(&track).style.background_color;
+    pico.get_mut(&track).style.background_color = if pico.hovered(&track) || dragging {
+        c + Color::rgba(0.06, 0.06, 0.06, 0.0)
+    } else {
+        c
+    };
+    track
+}
+
+// -------------------------
+// Range slider example widget
+// -------------------------
+
+/// Two handles on `track` select a sub range of `range`, with the span between them filled in.
+/// Each handle keeps its own drag state and is clamped against the other so they can't cross.
+/// Returns the low and high handles.
+pub fn range_slider(
+    pico: &mut Pico,
+    track: ItemIndex,
+    low: &mut f32,
+    high: &mut f32,
+    range: RangeInclusive<f32>,
+) -> (ItemIndex, ItemIndex) {
+    let (min, max) = (*range.start(), *range.end());
+    let bbox = pico.get(&track).get_bbox();
+    let track_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+    // Handles move every frame so they need ids that don't depend on their position
+    let track_id = pico.get(&track).get_spatial_id();
+    let handle_ids = [
+        child_id(track_id, "range_slider_low"),
+        child_id(track_id, "range_slider_high"),
+    ];
+
+    let cursor_value = |pico: &Pico, id: u64| -> Option<f32> {
+        let dragging = pico
+            .state
+            .get(&id)
+            .is_some_and(|state| state.drag.is_some());
+        let cursor = pico
+            .cursor_uv_position
+            .filter(|_| dragging && bbox.z > bbox.x)?;
+        let t = ((cursor.x - bbox.x) / (bbox.z - bbox.x)).clamp(0.0, 1.0);
+        Some(lerp(min, max, t))
+    };
+    // Only the dragged handle is clamped against the other, so dragging past it doesn't push it
+    if let Some(v) = cursor_value(pico, handle_ids[0]) {
+        *low = v.min(*high);
+    }
+    if let Some(v) = cursor_value(pico, handle_ids[1]) {
+        *high = v.max(*low);
+    }
+    *low = low.clamp(min, max);
+    *high = high.clamp(*low, max);
+
+    let to_t = |v: f32| {
+        if max > min {
+            ((v - min) / (max - min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    };
+    let (low_t, high_t) = (to_t(*low), to_t(*high));
+
+    let _guard = pico.stack_bypass();
+    pico.add(PicoItem {
+        x: Val::Percent(low_t * 100.0),
+        width: Val::Percent((high_t - low_t) * 100.0),
+        height: Val::Percent(100.0),
+        style: ItemStyle {
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.2),
+            ..default()
+        },
+        anchor: Anchor::CenterLeft,
+        anchor_parent: Anchor::CenterLeft,
+        parent: Some(track),
+        interactable: false,
+        ..default()
+    });
+    let handle_px = track_px.y * 2.0;
+    let mut handles = [track; 2];
+    for (handle, (t, id)) in handles
+        .iter_mut()
+        .zip([low_t, high_t].into_iter().zip(handle_ids))
+    {
+        *handle = pico.add(PicoItem {
+            x: Val::Px(t * track_px.x),
+            width: Val::Px(handle_px),
+            height: Val::Px(handle_px),
+            style: ItemStyle {
+                corner_radius: Val::Percent(50.0),
+                background_color: Color::rgb(0.9, 0.9, 0.9),
+                ..default()
+            },
+            anchor: Anchor::Center,
+            anchor_parent: Anchor::CenterLeft,
+            parent: Some(track),
+            spatial_id: Some(id), // Manually set id
+            focusable: true,
+            ..default()
+        });
+        let dragging = pico
+            .get_state(handle)
+            .is_some_and(|state| state.drag.is_some());
+        if pico.hovered(handle) || dragging {
+            let c = pico.get(handle).style.background_color;
+            pico.get_mut(handle).style.background_color = c + Color::rgba(0.06, 0.06, 0.06, 0.0);
+        }
+    }
+    (handles[0], handles[1])
+}
+
+// -------------------------
+// Knob example widget
+// -------------------------
+
+/// Radians from the lowest to the highest value, centered on straight up
+pub const KNOB_SWEEP: f32 = 1.5 * PI;
+/// Vertical drag, in uv of the window, to go through the whole range
+pub const KNOB_DRAG_DISTANCE: f32 = 0.3;
+pub const KNOB_TRACK_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.1);
+pub const KNOB_VALUE_COLOR: Color = Color::rgb(0.3, 0.6, 1.0);
+
+/// Dragging up increases the value, dragging down decreases it. The value is shown as an arc around
+/// the edge of the item with a line pointing at the current value. Use a round `corner_radius`
+/// for a round knob.
+pub fn knob(
+    pico: &mut Pico,
+    item: PicoItem,
+    value: &mut f32,
+    range: RangeInclusive<f32>,
+) -> ItemIndex {
+    let index = button(pico, item);
+    let (start, end) = (*range.start(), *range.end());
+    if let Some(drag) = pico.get_state(&index).and_then(|state| state.drag) {
+        *value -= drag.delta().y / KNOB_DRAG_DISTANCE * (end - start);
+    }
+    *value = value.clamp(start.min(end), start.max(end));
+    let t = if end != start {
+        (*value - start) / (end - start)
+    } else {
+        0.0
+    };
+
+    let bbox = pico.get(&index).get_bbox();
+    let item_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy()).max(Vec2::ONE);
+    let outer = item_px.min_element() * 0.5;
+    let inner = outer * 0.8;
+    // Angle 0 is straight up, increasing clockwise. Relative to the item.
+    let point = |angle: f32, radius: f32| {
+        (item_px * 0.5 + vec2(angle.sin(), -angle.cos()) * radius) / item_px
+    };
+    let add = |pico: &mut Pico, points: &[Vec2], color: Color| {
+        let polygon = pico.add_polygon(points, color, Some(index));
+        // So dragging on the arc drags the knob
+        pico.get_mut(&polygon).interactable = false;
+    };
+    let arc = |pico: &mut Pico, from: f32, to: f32, color: Color| {
+        // Convex segments, at most 1/32 of a turn each
+        let segments = ((to - from).abs() / (PI / 16.0)).ceil().max(1.0) as usize;
+        for i in 0..segments {
+            let a0 = lerp(from, to, i as f32 / segments as f32);
+            let a1 = lerp(from, to, (i + 1) as f32 / segments as f32);
+            let points = [
+                point(a0, inner),
+                point(a0, outer),
+                point(a1, outer),
+                point(a1, inner),
+            ];
+            add(pico, &points, color);
+        }
+    };
+    let start_angle = -KNOB_SWEEP * 0.5;
+    let value_angle = start_angle + KNOB_SWEEP * t;
+    arc(pico, start_angle, -start_angle, KNOB_TRACK_COLOR);
+    if t > 0.0 {
+        arc(pico, start_angle, value_angle, KNOB_VALUE_COLOR);
+    }
+
+    // Indicator line from near the center to the arc
+    let direction = vec2(value_angle.sin(), -value_angle.cos());
+    // 1px, perpendicular to the direction
+    let half_width = vec2(value_angle.cos(), value_angle.sin());
+    let a = item_px * 0.5 + direction * outer * 0.3;
+    let b = item_px * 0.5 + direction * inner;
+    let points = [
+        a - half_width,
+        b - half_width,
+        b + half_width,
+        a + half_width,
+    ];
+    add(pico, &points.map(|p| p / item_px), Color::WHITE);
+    index
+}
+
+// -------------------------
+// Sparkline example widget
+// -------------------------
+
+/// Pixels
+pub const SPARKLINE_LINE_WIDTH: f32 = 1.5;
+
+/// Line through `samples`, spread evenly across the item left to right. `range` is the values at the
+/// bottom and top of the item, None fits the min and max of the samples. Values outside the range
+/// are clamped. The line is not interactable, so hovering it hovers the item.
+pub fn sparkline(
+    pico: &mut Pico,
+    item: PicoItem,
+    samples: &[f32],
+    color: Color,
+    range: Option<RangeInclusive<f32>>,
+) -> ItemIndex {
+    let index = pico.add(item);
+    if samples.len() < 2 {
+        return index;
+    }
+    let (min, max) = range.map_or_else(
+        || {
+            samples
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), s| {
+                    (min.min(*s), max.max(*s))
+                })
+        },
+        |range| (*range.start(), *range.end()),
+    );
+    let bbox = pico.get(&index).get_bbox();
+    let item_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy()).max(Vec2::ONE);
+    // Pixels within the item, a flat line through the middle if there is no range
+    let points = samples
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let t = if max != min {
+                ((s - min) / (max - min)).clamp(0.0, 1.0)
+            } else {
+                0.5
+            };
+            vec2(i as f32 / (samples.len() - 1) as f32, 1.0 - t) * item_px
+        })
+        .collect::<Vec<_>>();
+    for segment in points.windows(2) {
+        let (a, b) = (segment[0], segment[1]);
+        let normal = (b - a).normalize_or_zero().perp() * SPARKLINE_LINE_WIDTH * 0.5;
+        let quad = [a - normal, b - normal, b + normal, a + normal];
+        let polygon = pico.add_polygon(&quad.map(|p| p / item_px), color, Some(index));
+        pico.get_mut(&polygon).interactable = false;
+    }
+    index
+}
+
+// -------------------------
+// Bar chart example widget
+// -------------------------
+
+pub const BAR_CHART_COLOR: Color = Color::rgb(0.3, 0.6, 1.0);
+/// Fraction of each bar's slot left empty between bars
+pub const BAR_CHART_GAP: f32 = 0.2;
+
+/// One bar per value in an hstack filling `parent`. Bars go from `baseline` (0.0 if None) up to
+/// their value, or down for values below it. The range is fit to the values and the baseline.
+/// `colors` are used in order and repeat, None uses `BAR_CHART_COLOR`.
+/// Returns the bar indices, for hover and tooltips.
+pub fn bar_chart(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    values: &[f32],
+    colors: Option<&[Color]>,
+    baseline: Option<f32>,
+) -> Vec<ItemIndex> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let baseline = baseline.unwrap_or(0.0);
+    let (min, max) = values.iter().fold((baseline, baseline), |(min, max), v| {
+        (min.min(*v), max.max(*v))
+    });
+    let span = (max - min).max(f32::EPSILON);
+    let slot = 100.0 / values.len() as f32;
+    let _guard = pico.hstack(
+        Val::Percent(slot * BAR_CHART_GAP * 0.5),
+        Val::Percent(slot * BAR_CHART_GAP),
+        false,
+        &parent,
+    );
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let top = value.max(baseline);
+            pico.add(PicoItem {
+                y: Val::Percent((max - top) / span * 100.0),
+                width: Val::Percent(slot * (1.0 - BAR_CHART_GAP)),
+                height: Val::Percent((value - baseline).abs() / span * 100.0),
+                style: ItemStyle {
+                    background_color: colors
+                        .filter(|colors| !colors.is_empty())
+                        .map_or(BAR_CHART_COLOR, |colors| colors[i % colors.len()]),
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent: Some(parent),
+                ..default()
+            })
+        })
+        .collect()
+}
+
+// -------------------------
+// Dropdown example widget
+// -------------------------
+
+/// A button showing the selected option, when clicked the options are listed below it,
+/// or above it if there isn't room below.
+/// `id` must be consistent across frames so the open state persists.
+/// Returns true if the selection changed.
+pub fn dropdown(
+    pico: &mut Pico,
+    mut item: PicoItem,
+    options: &[&str],
+    selected: &mut usize,
+    id: u64,
+) -> bool {
+    item.text = options.get(*selected).unwrap_or(&"").to_string();
+    item.spatial_id = Some(id); // Manually set id
+    let btn = button(pico, item);
+    let mut open = false;
+    if let Some(state) = pico.get_state(&btn) {
+        if let Some(storage) = &state.storage {
+            open = *storage.downcast_ref::<bool>().unwrap_or(&false);
+        }
+    }
+    if pico.clicked(&btn) {
+        open = !open;
+    }
+    let mut changed = false;
+    if open {
+        let mut any_hovered = pico.hovered(&btn);
+        let bg = pico.get(&btn).style.background_color;
+        let btn_bbox = pico.get(&btn).get_bbox();
+        let btn_size = btn_bbox.zw() - btn_bbox.xy();
+        let list_size = btn_size * vec2(1.0, options.len() as f32);
+        let _guard = pico.stack_bypass();
+        let list = pico.add(PicoItem {
+            uv_position: pico.popup_anchored_to(&btn, PopupSide::Below, list_size),
+            uv_size: list_size,
+            anchor: Anchor::TopLeft,
+            // In front of everything else
+            depth: Some(0.9),
+            ..default()
+        });
+        for (i, option) in options.iter().enumerate() {
+            let option_index = button(
+                pico,
+                PicoItem {
+                    text: option.to_string(),
+                    y: Val::Percent(100.0 * i as f32 / options.len() as f32),
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0 / options.len() as f32),
+                    style: ItemStyle {
+                        background_color: bg.with_a(1.0),
+                        ..default()
+                    },
+                    anchor: Anchor::TopLeft,
+                    parent: Some(list),
+                    ..default()
+                },
+            );
+            any_hovered |= pico.hovered(&option_index);
+            if pico.clicked(&option_index) {
+                *selected = i;
+                changed = true;
+                open = false;
+            }
+        }
+        // Close on click outside
+        if let Some(mouse_button_input) = &pico.mouse_button_input {
+            if !any_hovered && mouse_button_input.just_pressed(MouseButton::Left) {
+                open = false;
+            }
+        }
+    }
+    if let Some(state) = pico.get_state_mut(&btn) {
+        state.storage = Some(Box::new(open));
+    }
+    changed
+}
+
+// -------------------------
+// Window panel example widget
+// -------------------------
+
+/// A panel with a title bar that can be dragged to move it and a close button.
+/// `pos` is the top left of the panel and `size` its size, both uv of the window. The title bar is kept
+/// inside the window. `id` must be consistent across frames.
+/// Returns the content area below the title bar and if the close button was clicked.
+pub fn window_panel(
+    pico: &mut Pico,
+    id: u64,
+    title: &str,
+    pos: &mut Vec2,
+    size: Vec2,
+) -> (ItemIndex, bool) {
+    if let Some(state) = pico.state.get(&id) {
+        if let Some(drag) = state.drag {
+            *pos += drag.delta();
+        }
+    }
+    let title_height = pico.val_y(Val::Vh(3.0));
+    *pos = pos.clamp(
+        Vec2::ZERO,
+        (1.0 - vec2(size.x, title_height)).max(Vec2::ZERO),
+    );
+
+    let _guard = pico.stack_bypass();
+    let panel = pico.add(PicoItem {
+        uv_position: *pos,
+        uv_size: size,
+        anchor: Anchor::TopLeft,
+        style: ItemStyle {
+            background_color: Color::rgb(0.12, 0.12, 0.12),
+            border_color: Color::rgb(0.3, 0.3, 0.3),
+            border_width: Val::Px(1.0),
+            corner_radius: Val::Px(4.0),
+            ..default()
+        },
+        ..default()
+    });
+    let title_bar = pico.add(PicoItem {
+        text: title.to_string(),
+        width: Val::Percent(100.0),
+        height: Val::Vh(3.0),
+        style: ItemStyle {
+            background_color: Color::rgb(0.2, 0.2, 0.2),
+            multi_corner_radius: (Val::Px(4.0), Val::Px(4.0), Val::Px(0.0), Val::Px(0.0)),
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        parent: Some(panel),
+        spatial_id: Some(id), // Manually set id
+        ..default()
+    });
+    let close = button(
+        pico,
+        PicoItem {
+            text: "x".to_string(),
+            // Square, the height of the title bar
+            width: Val::Vh(3.0),
+            height: Val::Percent(100.0),
+            style: ItemStyle {
+                background_color: Color::rgb(0.2, 0.2, 0.2),
+                corner_radius: Val::Px(4.0),
+                ..default()
+            },
+            anchor: Anchor::TopRight,
+            anchor_parent: Anchor::TopRight,
+            parent: Some(title_bar),
+            ..default()
+        },
+    );
+    let content = pico.add(PicoItem {
+        y: Val::Vh(3.0),
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        // Relative to the panel
+        uv_size: vec2(0.0, -title_height / size.y.max(f32::EPSILON)),
+        anchor: Anchor::TopLeft,
+        parent: Some(panel),
+        ..default()
+    });
+    (content, pico.clicked(&close))
+}
+
+// -------------------------
+// Split pane example widget
+// -------------------------
+
+pub const SPLIT_PANE_DIVIDER_PX: f32 = 6.0;
+/// Smallest fraction of the parent either pane can be dragged to
+pub const SPLIT_PANE_MIN_RATIO: f32 = 0.1;
+
+/// Splits `parent` into two panes with a divider between them that can be dragged to change `ratio`,
+/// the fraction of the parent taken by the first pane. If `vertical` the first pane is above the
+/// second, otherwise to its left. `id` must be consistent across frames so the divider can be dragged.
+/// Returns the first and second pane.
+pub fn split_pane(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    ratio: &mut f32,
+    vertical: bool,
+    id: u64,
+) -> (ItemIndex, ItemIndex) {
+    let parent_bbox = pico.get(&parent).get_bbox();
+    let parent_size = (parent_bbox.zw() - parent_bbox.xy()).max(Vec2::splat(f32::EPSILON));
+    if let Some(state) = pico.state.get(&id) {
+        if let Some(drag) = state.drag {
+            let delta = drag.delta() / parent_size;
+            *ratio += if vertical { delta.y } else { delta.x };
+        }
+    }
+    *ratio = ratio.clamp(SPLIT_PANE_MIN_RATIO, 1.0 - SPLIT_PANE_MIN_RATIO);
+
+    // Percent along the split axis, the full size of the parent across it
+    let main = |percent: f32, uv: f32| {
+        if vertical {
+            (Val::Percent(100.0), Val::Percent(percent), vec2(0.0, uv))
+        } else {
+            (Val::Percent(percent), Val::Percent(100.0), vec2(uv, 0.0))
+        }
+    };
+    // Relative to the parent
+    let divider = if vertical {
+        pico.val_y(Val::Px(SPLIT_PANE_DIVIDER_PX)) / parent_size.y
+    } else {
+        pico.val_x(Val::Px(SPLIT_PANE_DIVIDER_PX)) / parent_size.x
+    };
+
+    let _guard = pico.stack_bypass();
+    let (width, height, uv_size) = main(*ratio * 100.0, -divider * 0.5);
+    let first = pico.add(PicoItem {
+        width,
+        height,
+        uv_size,
+        anchor: Anchor::TopLeft,
+        anchor_parent: Anchor::TopLeft,
+        parent: Some(parent),
+        ..default()
+    });
+    let (width, height, uv_size) = main((1.0 - *ratio) * 100.0, -divider * 0.5);
+    let second = pico.add(PicoItem {
+        width,
+        height,
+        uv_size,
+        anchor: Anchor::BottomRight,
+        anchor_parent: Anchor::BottomRight,
+        parent: Some(parent),
+        ..default()
+    });
+    let (width, height, uv_size) = main(0.0, divider);
+    let (x, y, anchor) = if vertical {
+        (
+            Val::Px(0.0),
+            Val::Percent(*ratio * 100.0),
+            Anchor::CenterLeft,
+        )
+    } else {
+        (
+            Val::Percent(*ratio * 100.0),
+            Val::Px(0.0),
+            Anchor::TopCenter,
+        )
+    };
+    button(
+        pico,
+        PicoItem {
+            x,
+            y,
+            width,
+            height,
+            uv_size,
+            style: ItemStyle {
+                background_color: Color::rgb(0.25, 0.25, 0.25),
+                ..default()
+            },
+            anchor,
+            anchor_parent: Anchor::TopLeft,
+            parent: Some(parent),
+            spatial_id: Some(id), // Manually set id
+            ..default()
+        },
+    );
+    (first, second)
+}
+
+// -------------------------
+// Context menu example widget
+// -------------------------
+
+/// Floating menu with a row per entry, its top left at `open_at` (uv of the window) moved as needed to
+/// stay inside the window. Typically opened at `pico.cursor_uv_position` when an item is
+/// `right_clicked()`. Closes on selection, on a click outside of it, or with escape.
+/// Returns the index of the clicked entry.
+pub fn context_menu(
+    pico: &mut Pico,
+    open_at: Vec2,
+    items: &[&str],
+    open: &mut bool,
+) -> Option<usize> {
+    if !*open || items.is_empty() {
+        return None;
+    }
+    let style = ItemStyle::default();
+    let padding = vec2(12.0, 6.0);
+    let mut size_px = Vec2::ZERO;
+    for item in items {
+        let text_size = pico.measure_text(item, &style.font, style.font_size);
+        size_px.x = size_px.x.max(text_size.x);
+        size_px.y = size_px.y.max(text_size.y);
+    }
+    let row_px = size_px.y.max(pico.val_y_px(Val::Vh(2.5))) + padding.y * 2.0;
+    let size_px = vec2(
+        size_px.x.max(pico.val_x_px(Val::Vh(10.0))) + padding.x * 2.0,
+        row_px * items.len() as f32,
+    );
+    let size = size_px / pico.window_size.max(Vec2::ONE);
+    let position = open_at.clamp(Vec2::ZERO, (1.0 - size).max(Vec2::ZERO));
+
+    let bg = Color::rgb(0.15, 0.15, 0.15);
+    let _guard = pico.stack_bypass();
+    let menu = pico.add(PicoItem {
+        uv_position: position,
+        uv_size: size,
+        anchor: Anchor::TopLeft,
+        style: ItemStyle {
+            background_color: bg,
+            border_color: Color::rgb(0.3, 0.3, 0.3),
+            border_width: Val::Px(1.0),
+            corner_radius: Val::Px(4.0),
+            ..default()
+        },
+        // In front of everything else
+        depth: Some(0.9),
+        ..default()
+    });
+    let mut any_hovered = pico.hovered(&menu);
+    let mut selected = None;
+    for (i, item) in items.iter().enumerate() {
+        let row = button(
+            pico,
+            PicoItem {
+                y: Val::Percent(100.0 * i as f32 / items.len() as f32),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0 / items.len() as f32),
+                style: ItemStyle {
+                    background_color: bg,
+                    corner_radius: Val::Px(4.0),
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent: Some(menu),
+                ..default()
+            },
+        );
+        pico.add(PicoItem {
+            text: item.to_string(),
+            x: Val::Px(padding.x),
+            anchor: Anchor::CenterLeft,
+            anchor_parent: Anchor::CenterLeft,
+            style: ItemStyle {
+                justify: JustifyText::Left,
+                anchor_text: Anchor::CenterLeft,
+                ..default()
+            },
+            parent: Some(row),
+            interactable: false,
+            ..default()
+        });
+        any_hovered |= pico.hovered(&row);
+        if pico.clicked(&row) {
+            selected = Some(i);
+        }
+    }
+
+    let clicked_outside = pico
+        .mouse_button_input
+        .as_ref()
+        .is_some_and(|input| !any_hovered && input.just_pressed(MouseButton::Left));
+    if selected.is_some() || clicked_outside || pico.key_just_pressed(KeyCode::Escape) {
+        *open = false;
+    }
+    selected
+}
+
+// -------------------------
+// Callout example widget
+// -------------------------
+
+/// Which side of the target the callout bubble is placed on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalloutDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Rough size in pixels of single font text, assumes a monospace font.
+pub fn estimate_text_size(text: &str, font_size_px: f32) -> Vec2 {
+    let lines = text.lines().count().max(1);
+    let max_chars = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+    vec2(
+        max_chars as f32 * font_size_px * 0.6,
+        lines as f32 * font_size_px * 1.2,
+    )
+}
+
+/// Speech bubble sized to fit `text` with a tail pointing at `target` (uv of the window).
+/// The bubble is kept on screen while the tail still points at the target.
+pub fn callout(
+    pico: &mut Pico,
+    target: Vec2,
+    text: &str,
+    direction: CalloutDirection,
+) -> ItemIndex {
+    let bg = Color::rgb(0.95, 0.95, 0.9);
+    let font_size = Val::Vh(2.0);
+    let padding_px = pico.val_y_px(Val::Vh(1.5));
+    let tail_px = pico.val_y_px(Val::Vh(2.0));
+    let size_px = estimate_text_size(text, pico.val_y_px(font_size)) + padding_px * 2.0;
+    let size = size_px / pico.window_size;
+    let tail = vec2(tail_px, tail_px) / pico.window_size;
+
+    let dir = match direction {
+        CalloutDirection::Up => vec2(0.0, -1.0),
+        CalloutDirection::Down => vec2(0.0, 1.0),
+        CalloutDirection::Left => vec2(-1.0, 0.0),
+        CalloutDirection::Right => vec2(1.0, 0.0),
+    };
+    // Top left of the bubble
+    let center = target + dir * (size * 0.5 + tail);
+    let top_left = (center - size * 0.5).clamp(Vec2::ZERO, (Vec2::ONE - size).max(Vec2::ZERO));
+
+    let bubble = pico.add(PicoItem {
+        text: text.to_string(),
+        uv_position: top_left,
+        uv_size: size,
+        style: ItemStyle {
+            corner_radius: Val::Px(padding_px),
+            background_color: bg,
+            text_color: Color::BLACK,
+            font_size,
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        depth: Some(0.9),
+        ..default()
+    });
+
+    // Base of the tail on the side of the bubble facing the target,
+    // kept away from the rounded corners.
+    let bbox = pico.get(&bubble).get_bbox();
+    let inset = vec2(padding_px, padding_px) / pico.window_size + tail * 0.5;
+    let base_center = target.clamp(
+        bbox.xy() + inset,
+        (bbox.zw() - inset).max(bbox.xy() + inset),
+    );
+    let (a, b) = match direction {
+        CalloutDirection::Up => (
+            vec2(base_center.x - tail.x * 0.5, bbox.w),
+            vec2(base_center.x + tail.x * 0.5, bbox.w),
+        ),
+        CalloutDirection::Down => (
+            vec2(base_center.x - tail.x * 0.5, bbox.y),
+            vec2(base_center.x + tail.x * 0.5, bbox.y),
+        ),
+        CalloutDirection::Left => (
+            vec2(bbox.z, base_center.y - tail.y * 0.5),
+            vec2(bbox.z, base_center.y + tail.y * 0.5),
+        ),
+        CalloutDirection::Right => (
+            vec2(bbox.x, base_center.y - tail.y * 0.5),
+            vec2(bbox.x, base_center.y + tail.y * 0.5),
+        ),
+    };
+    // Parent to the bubble so the tail has the same depth
+    let to_bubble_uv = |p: Vec2| (p - bbox.xy()) / (bbox.zw() - bbox.xy());
+    pico.add_polygon(
+        &[to_bubble_uv(a), to_bubble_uv(b), to_bubble_uv(target)],
+        bg,
+        Some(bubble),
+    );
+    bubble
+}
+
+/// Inserts line breaks between words so lines are at most `max_chars` long, where possible.
+pub fn wrap_text(text: &str, max_chars: usize) -> String {
+    let mut out = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut line_len = 0;
+        for word in line.split_whitespace() {
+            let word_len = word.chars().count();
+            if line_len > 0 && line_len + 1 + word_len > max_chars {
+                out.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                out.push(' ');
+                line_len += 1;
+            }
+            out += word;
+            line_len += word_len;
+        }
+    }
+    out
+}
+
+// -------------------------
+// Help icon example widget
+// -------------------------
+
+/// A small "?" circle at the right of `parent`. Hovering the icon shows `text` as its tooltip, wrapped to
+/// a readable width. The delay is `Pico::tooltip_delay`, the same as other tooltips.
+pub fn help_icon(pico: &mut Pico, parent: ItemIndex, text: &str) -> ItemIndex {
+    let _guard = pico.stack_bypass();
+    pico.add(PicoItem {
+        text: "?".to_string(),
+        x: Val::Vh(0.5),
+        width: Val::Vh(2.0),
+        height: Val::Vh(2.0),
+        style: ItemStyle {
+            corner_radius: Val::Percent(50.0),
+            background_color: Color::rgb(0.3, 0.3, 0.35),
+            font_size: Val::Vh(1.6),
+            tooltip: Some(wrap_text(text, 40)),
+            ..default()
+        },
+        anchor: Anchor::CenterRight,
+        anchor_parent: Anchor::CenterRight,
+        parent: Some(parent),
+        ..default()
+    })
+}
+
+// -------------------------
+// Spotlight example widget
+// -------------------------
+
+/// Dims the whole window except around `target` and points a callout at it with `text`.
+/// The dimmed area blocks clicks so only the highlighted item can be interacted with.
+/// Returns the callout index.
+pub fn spotlight(pico: &mut Pico, target: &ItemIndex, text: &str) -> ItemIndex {
+    let dim = Color::rgba(0.0, 0.0, 0.0, 0.7);
+    let margin = vec2(pico.val_x(Val::Vh(0.5)), pico.val_y(Val::Vh(0.5)));
+    let bbox = pico.get(target).get_bbox();
+    let hole_min = (bbox.xy() - margin).clamp(Vec2::ZERO, Vec2::ONE);
+    let hole_max = (bbox.zw() + margin).clamp(Vec2::ZERO, Vec2::ONE);
+    // Above, below, left, right of the hole
+    for (min, max) in [
+        (vec2(0.0, 0.0), vec2(1.0, hole_min.y)),
+        (vec2(0.0, hole_max.y), vec2(1.0, 1.0)),
+        (vec2(0.0, hole_min.y), vec2(hole_min.x, hole_max.y)),
+        (vec2(hole_max.x, hole_min.y), vec2(1.0, hole_max.y)),
+    ] {
+        let _guard = pico.stack_bypass();
+        pico.add(PicoItem {
+            uv_position: min,
+            uv_size: max - min,
+            style: ItemStyle {
+                background_color: dim,
+                ..default()
+            },
+            anchor: Anchor::TopLeft,
+            depth: Some(0.8),
+            ..default()
+        });
+    }
+    let center = (bbox.xy() + bbox.zw()) * 0.5;
+    if center.y > 0.5 {
+        callout(pico, vec2(center.x, hole_min.y), text, CalloutDirection::Up)
+    } else {
+        callout(
+            pico,
+            vec2(center.x, hole_max.y),
+            text,
+            CalloutDirection::Down,
+        )
+    }
+}
+
+// -------------------------
+// Collapsing section example widget
+// -------------------------
+
+/// Fraction of the open/close animation per second
+pub const COLLAPSING_SPEED: f32 = 5.0;
+
+/// A header that can be clicked to expand or collapse the content below it.
+/// Content added in `add_contents` is parented to a `content_height` tall item and clipped
+/// while the section animates open or closed. `id` must be consistent across frames.
+/// Returns true if the section is open.
+pub fn collapsing(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    title: &str,
+    content_height: Val,
+    id: u64,
+    add_contents: impl FnOnce(&mut Pico, ItemIndex),
+) -> bool {
+    let header = button(
+        pico,
+        PicoItem {
+            // Leave room for the triangle
+            text: format!("     {title}"),
+            width: Val::Percent(100.0),
+            height: Val::Vh(3.0),
+            style: ItemStyle {
+                background_color: Color::rgb(0.2, 0.2, 0.2),
+                anchor_text: Anchor::CenterLeft,
+                justify: JustifyText::Left,
+                ..default()
+            },
+            anchor: Anchor::TopLeft,
+            parent: Some(parent),
+            spatial_id: Some(id), // Manually set id
+            ..default()
+        },
+    );
+
+    let key = persistent_key("collapsing", id);
+    let (mut open, mut t) = match pico
+        .get_state(&header)
+        .and_then(|state| state.storage.as_ref())
+        .and_then(|storage| storage.downcast_ref::<(bool, f32)>())
+    {
+        Some(data) => *data,
+        // Restore without animating when it first appears
+        None => {
+            let open = pico.get_persistent(&key).unwrap_or(false);
+            (open, if open { 1.0 } else { 0.0 })
+        }
+    };
+    if pico.clicked(&header) {
+        open = !open;
+        pico.set_persistent(&key, &open);
+    }
+    let eased_t = animate_collapse(pico, open, &mut t);
+    if let Some(state) = pico.get_state_mut(&header) {
+        state.storage = Some(Box::new((open, t)));
+    }
+
+    disclosure_triangle(pico, header, 0.0, eased_t);
+
+    let parent_bbox = pico.get(&parent).get_bbox();
+    let content_px =
+        pico.valp_y(content_height, parent_bbox.zw() - parent_bbox.xy()) * pico.window_size.y;
+    if let Some((content, _guards)) = collapsing_content(pico, parent, content_px, eased_t) {
+        add_contents(pico, content);
+    }
+    open
+}
+
+/// Moves `t` toward 1 while `open` and toward 0 while closed at `COLLAPSING_SPEED`.
+/// Returns `t` eased for display.
+fn animate_collapse(pico: &Pico, open: bool, t: &mut f32) -> f32 {
+    let target = if open { 1.0 } else { 0.0 };
+    let step = COLLAPSING_SPEED * pico.delta_seconds;
+    *t += (target - *t).clamp(-step, step);
+    *t * *t * (3.0 - 2.0 * *t)
+}
+
+/// A `content_px` tall area in `parent` revealed by `eased_t`. While any of it is visible returns
+/// the content item to add to and the guards that clip it, hold them while adding the content.
+fn collapsing_content(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    content_px: f32,
+    eased_t: f32,
+) -> Option<(ItemIndex, [Guard; 2])> {
+    // Moves the following siblings in a stack as it opens
+    let clip_area = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        height: Val::Px(content_px * eased_t),
+        anchor: Anchor::TopLeft,
+        parent: Some(parent),
+        ..default()
+    });
+    if eased_t <= 0.0 {
+        return None;
+    }
+    let guard = pico.stack_bypass();
+    let clip_guard = pico.clip(&clip_area);
+    let content = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        // Full height so content isn't squished during the animation, just clipped
+        height: Val::Px(content_px),
+        anchor: Anchor::TopLeft,
+        parent: Some(clip_area),
+        ..default()
+    });
+    Some((content, [clip_guard, guard]))
+}
+
+/// Triangle pointing right when `t` is 0 and down when `t` is 1.
+/// Centered in a square the height of `item`, offset `x_px` pixels from its left edge.
+fn disclosure_triangle(pico: &mut Pico, item: ItemIndex, x_px: f32, t: f32) -> ItemIndex {
+    let _guard = pico.stack_bypass();
+    let bbox = pico.get(&item).get_bbox();
+    let item_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+    let size = item_px.y * 0.25;
+    let center = vec2(x_px + item_px.y * 0.5, item_px.y * 0.5);
+    let rotation = Mat2::from_angle(t * std::f32::consts::FRAC_PI_2);
+    let points = [vec2(-0.8, -1.0), vec2(1.0, 0.0), vec2(-0.8, 1.0)]
+        .map(|p| (center + rotation * p * size) / item_px);
+    pico.add_polygon(&points, Color::WHITE, Some(item))
+}
+
+// -------------------------
+// Tree view example widget
+// -------------------------
+
+/// Nested rows that can be expanded and collapsed, with a guide line for each level of indentation.
+/// Children open and close with the same animation as `collapsing()`. Clicking a row selects and
+/// focuses it. While the selected row has focus the up and down arrow keys move the selection
+/// through the visible rows, and left and right collapse and expand the selected node.
+/// Rows are stacked from the top of `container`, to scroll a large tree put it in a clipped parent
+/// and offset it with `y`.
+pub struct Tree<'a> {
+    pub pico: &'a mut Pico,
+    pub container: ItemIndex,
+    /// Id of the selected node
+    pub selected: Option<u64>,
+    pub selection_changed: bool,
+    pub row_height: Val,
+    pub indent: Val,
+    depth: usize,
+    /// Rows are added to the content of the node they are in
+    parent: ItemIndex,
+    /// If the arrow keys move the selection this frame
+    keys_active: bool,
+    /// Number of nodes around the current row that are closing, their rows can't be navigated to
+    closing: usize,
+    /// Rows added so far, for sizing the content of each node
+    rows: usize,
+    /// Ids of the rows added this frame, in order, used for keyboard navigation
+    visible: Vec<u64>,
+    _guard: Guard,
+}
+
+/// `id` must be consistent across frames so the selection persists.
+/// Node ids must also be consistent and unique within the tree.
+pub fn tree(pico: &mut Pico, parent: ItemIndex, id: u64) -> Tree<'_> {
+    let container = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        anchor: Anchor::TopLeft,
+        parent: Some(parent),
+        spatial_id: Some(id), // Manually set id
+        ..default()
+    });
+    // Visible rows are from last frame since rows are added after this.
+    let mut selected = None;
+    let mut last_visible = Vec::new();
+    if let Some(state) = pico.get_state_mut(&container) {
+        if let Some(storage) = &mut state.storage {
+            if let Some(data) = storage.downcast_mut::<(Option<u64>, Vec<u64>)>() {
+                selected = data.0;
+                last_visible = data.1.clone();
+            }
+        }
+    }
+    // Typing elsewhere shouldn't move the selection
+    let keys_active =
+        selected.is_some() && pico.focused == selected && !pico.wants_keyboard_input();
+    let mut selection_changed = false;
+    if let Some(current) = selected.filter(|_| keys_active) {
+        if pico.key_just_pressed(KeyCode::Escape) {
+            selected = None;
+        } else if let Some(i) = last_visible.iter().position(|id| *id == current) {
+            if pico.key_just_pressed(KeyCode::ArrowDown) && i + 1 < last_visible.len() {
+                selected = Some(last_visible[i + 1]);
+            }
+            if pico.key_just_pressed(KeyCode::ArrowUp) && i > 0 {
+                selected = Some(last_visible[i - 1]);
+            }
+        }
+        selection_changed = selected != Some(current);
+    }
+    let guard = pico.vstack(Val::Px(0.0), Val::Px(0.0), false, &container);
+    Tree {
+        pico,
+        container,
+        selected,
+        selection_changed,
+        row_height: Val::Vh(3.0),
+        indent: Val::Vh(2.0),
+        depth: 0,
+        parent: container,
+        keys_active,
+        closing: 0,
+        rows: 0,
+        visible: Vec::new(),
+        _guard: guard,
+    }
+}
+
+impl<'a> Tree<'a> {
+    /// A node that can be expanded. `add_children` is only called while the node is open or
+    /// animating, nodes added in it are indented one level further.
+    pub fn node(
+        &mut self,
+        label: &str,
+        id: u64,
+        add_children: impl FnOnce(&mut Tree),
+    ) -> ItemIndex {
+        let key = persistent_key("tree_node", id);
+        // Expanded, animation t, and the number of rows the children had last frame
+        let (mut expanded, mut t, mut child_rows) = match self
+            .pico
+            .state
+            .get(&id)
+            .and_then(|state| state.storage.as_ref())
+            .and_then(|storage| storage.downcast_ref::<(bool, f32, usize)>())
+        {
+            Some(data) => *data,
+            // Restore without animating when it first appears
+            None => {
+                let expanded = self.pico.get_persistent(&key).unwrap_or(false);
+                (expanded, if expanded { 1.0 } else { 0.0 }, 0)
+            }
+        };
+        let was_expanded = expanded;
+        let (row, _) = self.row(label, id, true, &mut expanded);
+        if expanded != was_expanded {
+            self.pico.set_persistent(&key, &expanded);
+        }
+        let eased_t = animate_collapse(self.pico, expanded, &mut t);
+        self.disclosure_triangle(row, eased_t);
+
+        let content_px = child_rows as f32 * self.pico.val_y_px(self.row_height);
+        if let Some((content, _guards)) =
+            collapsing_content(self.pico, self.parent, content_px, eased_t)
+        {
+            let parent = std::mem::replace(&mut self.parent, content);
+            let closing = usize::from(!expanded);
+            let rows = self.rows;
+            self.closing += closing;
+            self.depth += 1;
+            {
+                let _guard = self
+                    .pico
+                    .vstack(Val::Px(0.0), Val::Px(0.0), false, &content);
+                add_children(self);
+            }
+            self.depth -= 1;
+            self.closing -= closing;
+            self.parent = parent;
+            child_rows = self.rows - rows;
+        }
+        if let Some(state) = self.pico.get_state_mut(&row) {
+            state.storage = Some(Box::new((expanded, t, child_rows)));
+        }
+        row
+    }
+
+    /// A node without children
+    pub fn leaf(&mut self, label: &str, id: u64) -> ItemIndex {
+        self.row(label, id, false, &mut false).0
+    }
+
+    /// A node indented `depth` levels, for walking a flattened hierarchy like a scene graph instead of
+    /// nesting `add_children` closures. The caller keeps the expanded state and only adds the children
+    /// that follow while `expanded` is true. Returns the row and if it was clicked.
+    pub fn flat_node(
+        &mut self,
+        depth: usize,
+        label: &str,
+        id: u64,
+        has_children: bool,
+        expanded: &mut bool,
+    ) -> (ItemIndex, bool) {
+        let parent_depth = std::mem::replace(&mut self.depth, depth);
+        let result = self.row(label, id, has_children, expanded);
+        if has_children {
+            self.disclosure_triangle(result.0, if *expanded { 1.0 } else { 0.0 });
+        }
+        self.depth = parent_depth;
+        result
+    }
+
+    fn disclosure_triangle(&mut self, row: ItemIndex, t: f32) {
+        let toggle_px = self.pico.val_y_px(self.row_height);
+        disclosure_triangle(self.pico, row, -toggle_px, t);
+    }
+
+    /// Returns the row and if it was clicked
+    fn row(
+        &mut self,
+        label: &str,
+        id: u64,
+        has_children: bool,
+        expanded: &mut bool,
+    ) -> (ItemIndex, bool) {
+        let indent_px = self.pico.val_y_px(self.indent);
+        let toggle_px = self.pico.val_y_px(self.row_height);
+        let parent_bbox = self.pico.get(&self.parent).get_bbox();
+        let parent_px = self
+            .pico
+            .uv_scale_to_px(parent_bbox.zw() - parent_bbox.xy());
+        let offset_px = self.depth as f32 * indent_px + toggle_px;
+        let selected = self.selected == Some(id);
+
+        let row = button(
+            self.pico,
+            PicoItem {
+                text: label.to_string(),
+                x: Val::Px(offset_px),
+                width: Val::Percent(100.0),
+                height: self.row_height,
+                // Shrink by the indentation so rows end at the right edge of the container
+                uv_size: vec2(-offset_px / parent_px.x.max(1.0), 0.0),
+                style: ItemStyle {
+                    background_color: if selected {
+                        Color::rgb(0.2, 0.3, 0.5)
+                    } else {
+                        Color::NONE
+                    },
+                    anchor_text: Anchor::CenterLeft,
+                    justify: JustifyText::Left,
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent: Some(self.parent),
+                spatial_id: Some(id), // Manually set id
+                ..default()
+            },
+        );
+        self.rows += 1;
+        if self.closing == 0 {
+            self.visible.push(id);
+        }
+        if selected && self.selection_changed {
+            self.pico.request_focus(&row);
+        }
+
+        let _guard = self.pico.stack_bypass();
+        for level in 0..self.depth {
+            self.pico.add(PicoItem {
+                x: Val::Px((level as f32 + 0.5) * indent_px + toggle_px * 0.5 - offset_px),
+                width: Val::Px(1.0),
+                height: Val::Percent(100.0),
+                style: ItemStyle {
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.15),
+                    ..default()
+                },
+                anchor: Anchor::TopCenter,
+                anchor_parent: Anchor::TopLeft,
+                parent: Some(row),
+                interactable: false,
+                ..default()
+            });
+        }
+
+        if has_children {
+            let toggle = self.pico.add(PicoItem {
+                x: Val::Px(-toggle_px),
+                width: Val::Px(toggle_px),
+                height: Val::Percent(100.0),
+                anchor: Anchor::TopLeft,
+                anchor_parent: Anchor::TopLeft,
+                parent: Some(row),
+                ..default()
+            });
+            if self.pico.clicked(&toggle) {
+                *expanded = !*expanded;
+            }
+            if selected && self.keys_active && self.pico.key_just_pressed(KeyCode::ArrowRight) {
+                *expanded = true;
+            }
+            if selected && self.keys_active && self.pico.key_just_pressed(KeyCode::ArrowLeft) {
+                *expanded = false;
+            }
+        }
+
+        let clicked = self.pico.clicked(&row);
+        if clicked && !selected {
+            self.selected = Some(id);
+            self.selection_changed = true;
+        }
+        (row, clicked)
+    }
+}
+
+impl<'a> Drop for Tree<'a> {
+    fn drop(&mut self) {
+        // Keep the visible rows for keyboard navigation next frame
+        let data = (self.selected, std::mem::take(&mut self.visible));
+        if let Some(state) = self.pico.get_state_mut(&self.container) {
+            state.storage = Some(Box::new(data));
+        }
+    }
+}
+
+// -------------------------
+// Horizontal ruler example widget
+// -------------------------
+
+/// Width is relative to parent. Height-y parent is removed so height is only relative to screen height
+/// so HRs are of a consistent height for the same input height.
+pub fn hr(pico: &mut Pico, width: Val, height: Val, parent: Option<ItemIndex>) -> ItemIndex {
+    pico.add(PicoItem {
+        uv_position: vec2(0.5, 0.0),
+        width,
+        height,
+        style: ItemStyle {
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.04),
+            ..default()
+        },
+        anchor: Anchor::TopCenter,
+        parent,
+        ..default()
+    })
+}
+
+// -------------------------
+// Text input character filter
+// -------------------------
+
+/// Decides which characters a text field will accept. Rejected characters are silently dropped.
+#[derive(Clone, Copy)]
+pub enum CharFilter {
+    /// Digits, '.', and '-'
+    Numeric,
+    Alphanumeric,
+    Custom(fn(char) -> bool),
+}
+
+impl CharFilter {
+    pub fn accepts(&self, c: char) -> bool {
+        match self {
+            CharFilter::Numeric => c.is_ascii_digit() || c == '.' || c == '-',
+            CharFilter::Alphanumeric => c.is_alphanumeric(),
+            CharFilter::Custom(f) => f(c),
+        }
+    }
+
+    /// Returns only the accepted characters of `s`, for validating pasted text.
+    pub fn filter_str(&self, s: &str) -> String {
+        s.chars().filter(|c| self.accepts(*c)).collect()
+    }
+}
+
+// -------------------------
+// Text input example widget
+// -------------------------
+
+/// Seconds the text cursor is shown, then hidden, while editing
+pub const TEXT_CURSOR_BLINK_TIME: f32 = 0.5;
+
+/// In progress edit of a text field, shared by `text_input()` and `drag_value()`
+struct TextEdit {
+    text: String,
+    /// In chars
+    cursor: usize,
+    /// Set when editing starts so typing replaces the text, cleared by moving the cursor
+    select_all: bool,
+    /// Seconds since the cursor moved, for blinking
+    blink: f32,
+}
+
+enum TextEditResult {
+    Editing,
+    Commit,
+    Cancel,
+}
+
+impl TextEdit {
+    fn new(text: String) -> Self {
+        TextEdit {
+            cursor: text.chars().count(),
+            text,
+            select_all: true,
+            blink: 0.0,
+        }
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(i, _)| i)
+    }
+
+    fn clear_if_selected(&mut self) {
+        if self.select_all {
+            self.text.clear();
+            self.cursor = 0;
+            self.select_all = false;
+        }
+    }
+
+    fn update(
+        &mut self,
+        pico: &Pico,
+        char_events: &mut EventReader<ReceivedCharacter>,
+        filter: CharFilter,
+    ) -> TextEditResult {
+        self.blink += pico.delta_seconds;
+        let len = self.text.chars().count();
+        let cursor = if pico.key_just_pressed(KeyCode::ArrowLeft) {
+            Some(self.cursor.saturating_sub(1))
+        } else if pico.key_just_pressed(KeyCode::ArrowRight) {
+            Some((self.cursor + 1).min(len))
+        } else if pico.key_just_pressed(KeyCode::Home) {
+            Some(0)
+        } else if pico.key_just_pressed(KeyCode::End) {
+            Some(len)
+        } else {
+            None
+        };
+        if let Some(cursor) = cursor {
+            self.cursor = cursor;
+            self.select_all = false;
+            self.blink = 0.0;
+        }
+
+        let backspace = char::from_u32(0x08).unwrap();
+        let esc = char::from_u32(0x1b).unwrap();
+        let enter = '\r';
+        for e in char_events.read() {
+            // A single event can hold more than one char (pasted text, IME)
+            for char in e.char.chars() {
+                if char == esc {
+                    return TextEditResult::Cancel;
+                } else if char == enter {
+                    return TextEditResult::Commit;
+                } else if char == backspace {
+                    if self.select_all {
+                        self.clear_if_selected();
+                    } else if self.cursor > 0 {
+                        self.cursor -= 1;
+                        let i = self.byte_index(self.cursor);
+                        self.text.remove(i);
+                    }
+                } else if !char.is_control() && filter.accepts(char) {
+                    self.clear_if_selected();
+                    let i = self.byte_index(self.cursor);
+                    self.text.insert(i, char);
+                    self.cursor += 1;
+                }
+                self.blink = 0.0;
+            }
+        }
+        TextEditResult::Editing
+    }
+
+    /// The text with the blinking cursor
+    fn display(&self) -> String {
+        let mut s = self.text.clone();
+        if (self.blink / TEXT_CURSOR_BLINK_TIME) % 2.0 < 1.0 {
+            s.insert(self.byte_index(self.cursor), '|');
+        }
+        s
+    }
+}
+
+fn take_text_edit(pico: &mut Pico, index: &ItemIndex) -> Option<TextEdit> {
+    let state = pico.get_state_mut(index)?;
+    let storage = state.storage.as_mut()?;
+    storage.downcast_mut::<Option<TextEdit>>()?.take()
+}
+
+/// Also marks the item as selected while editing, see `Pico::wants_keyboard_input()`
+fn store_text_edit(pico: &mut Pico, index: &ItemIndex, edit: Option<TextEdit>) {
+    if let Some(state) = pico.get_state_mut(index) {
+        state.selected = edit.is_some();
+        state.storage = Some(Box::new(edit));
     }
-    pico.get_mut(&index).style.background_color = if pico.hovered(&index) {
-        c + Color::rgb(0.08, 0.08, 0.08)
-    } else {
-        c
-    };
-    index
 }
 
-// -------------------------
-// Horizontal ruler example widget
-// -------------------------
+/// A single line text field in `index`. Clicking it (or tabbing to it) focuses it and starts
+/// editing with all of the text selected, so typing replaces it. Left, right, home, and end move
+/// the cursor. Enter or losing focus commits the edit to `buffer`, escape cancels it.
+/// Typed characters that `filter` rejects are dropped.
+/// Returns true when a committed edit changed `buffer`.
+pub fn text_input(
+    pico: &mut Pico,
+    index: ItemIndex,
+    buffer: &mut String,
+    char_events: &mut EventReader<ReceivedCharacter>,
+    filter: CharFilter,
+) -> bool {
+    pico.get_mut(&index).focusable = true;
+    let focused = pico.has_focus(&index);
+    let mut edit = take_text_edit(pico, &index);
+    if edit.is_none() && focused {
+        edit = Some(TextEdit::new(buffer.clone()));
+    }
+    let mut changed = false;
+    if let Some(e) = &mut edit {
+        let result = if focused {
+            e.update(pico, char_events, filter)
+        } else {
+            TextEditResult::Commit
+        };
+        match result {
+            TextEditResult::Editing => (),
+            TextEditResult::Commit => {
+                changed = e.text != *buffer;
+                *buffer = std::mem::take(&mut e.text);
+                edit = None;
+            }
+            TextEditResult::Cancel => edit = None,
+        }
+        if edit.is_none() && focused {
+            pico.focused = None;
+        }
+    }
 
-/// Width is relative to parent. Height-y parent is removed so height is only relative to screen height
-/// so HRs are of a consistent height for the same input height.
-pub fn hr(pico: &mut Pico, width: Val, height: Val, parent: Option<ItemIndex>) -> ItemIndex {
-    pico.add(PicoItem {
-        uv_position: vec2(0.5, 0.0),
-        width,
-        height,
-        style: ItemStyle {
-            background_color: Color::rgba(1.0, 1.0, 1.0, 0.04),
-            ..default()
-        },
-        anchor: Anchor::TopCenter,
-        parent,
-        ..default()
-    })
+    let item = pico.get_mut(&index);
+    if let Some(e) = &edit {
+        item.text = e.display();
+        if e.select_all {
+            item.style.background_color =
+                item.style.background_color + Color::rgba(0.25, 0.25, 0.25, 0.0);
+        }
+    } else {
+        item.text = buffer.clone();
+    }
+    store_text_edit(pico, &index, edit);
+    changed
 }
 
 // -------------------------
 // Value drag example widget
 // -------------------------
 
+/// Dragging changes the value by `scale` per window uv. With `char_input_events` it is focusable, and
+/// while focused, after releasing the mouse without dragging or tabbing to it, the value is edited as
+/// text, keeping the characters `filter` accepts. Losing focus commits the edit.
 #[allow(clippy::too_many_arguments)]
 pub fn drag_value(
     pico: &mut Pico,
@@ -83,6 +1984,7 @@ pub fn drag_value(
     value: f32,
     drag_index: ItemIndex,
     char_input_events: Option<&mut EventReader<ReceivedCharacter>>,
+    filter: CharFilter,
 ) -> f32 {
     let mut value = value;
     let mut drag_bg = pico.get_mut(&drag_index).style.background_color;
@@ -96,80 +1998,43 @@ pub fn drag_value(
         }
     };
     if let Some(char_input_events) = char_input_events {
-        let mouse_just_pressed = if let Some(mouse_button_input) = &pico.mouse_button_input {
-            mouse_button_input.any_just_pressed([
-                MouseButton::Left,
-                MouseButton::Right,
-                MouseButton::Middle,
-            ])
-        } else {
-            false
-        };
-        let mut current_string = None;
-        let released = pico.released(&drag_index);
-        let mut reset = false;
-        let mut apply = false;
-        let mut selected = false;
-        if let Some(state) = pico.get_state_mut(&drag_index) {
-            let mut just_selected = false;
-            if state.storage.is_none() {
-                state.storage = Some(Box::<String>::default());
-            }
-            if !dragging && released {
-                state.selected = true;
-                just_selected = true;
-            }
-            if state.selected {
-                selected = true;
-                let backspace = char::from_u32(0x08).unwrap();
-                let esc = char::from_u32(0x1b).unwrap();
-                let enter = '\r';
-                if let Some(storage) = &mut state.storage {
-                    let s = storage.downcast_mut::<String>().unwrap();
-                    if mouse_just_pressed && !just_selected {
-                        apply = true;
-                    } else {
-                        // TODO: usually when a text field like this is first selected it would have all of the
-                        // text in the field selected, so typing anything would overwrite the existing value
-                        // or the cursor could be moved to preserve the value.
-                        //if just_selected {
-                        //    // TODO user or auto precision
-                        //    *s = format!("{:.2}", value);
-                        //}
-                        for e in char_input_events.read() {
-                            let char = e.char.chars().next().unwrap();
-                            if char == esc {
-                                reset = true;
-                            } else if char == backspace {
-                                s.pop();
-                            } else if char == enter {
-                                apply = true;
-                                break;
-                            } else if char.is_ascii_digit() || char == '.' || char == '-' {
-                                s.push(char);
-                            }
-                        }
-                        current_string = Some(s.clone());
-                    }
-                    if apply {
-                        if let Ok(parse_val) = s.parse::<f32>() {
-                            value = parse_val;
-                        }
-                        reset = true;
-                    }
-                    if reset {
-                        state.selected = false;
-                        *s = String::new();
+        pico.get_mut(&drag_index).focusable = true;
+        let focused = pico.has_focus(&drag_index);
+        let mut edit = take_text_edit(pico, &drag_index);
+        // Clicking focuses it without showing the focus outline, so editing starts on release unless
+        // it was dragged. Tab shows the outline and starts editing right away.
+        if edit.is_none()
+            && focused
+            && (pico.focus_visible || (!dragging && pico.released(&drag_index)))
+        {
+            // TODO user or auto precision
+            edit = Some(TextEdit::new(value.to_string()));
+        }
+        if let Some(e) = &mut edit {
+            let result = if focused {
+                e.update(pico, char_input_events, filter)
+            } else {
+                TextEditResult::Commit
+            };
+            match result {
+                TextEditResult::Editing => (),
+                TextEditResult::Commit => {
+                    if let Ok(parse_val) = e.text.parse::<f32>() {
+                        value = parse_val;
                     }
+                    edit = None;
                 }
+                TextEditResult::Cancel => edit = None,
+            }
+            if edit.is_none() && focused {
+                pico.focused = None;
             }
         }
-        if let Some(current_string) = current_string {
-            pico.get_mut(&drag_index).text = current_string + "|";
-        }
-        if selected {
+        if let Some(e) = &edit {
+            pico.get_mut(&drag_index).text = e.display();
             drag_bg = drag_bg + Color::rgba(0.25, 0.25, 0.25, 0.0);
         }
+        store_text_edit(pico, &drag_index, edit);
     }
     pico.get_mut(&drag_index).style.background_color = if pico.hovered(&drag_index) || dragging {
         drag_bg + Color::rgba(0.06, 0.06, 0.06, 0.0)
@@ -222,7 +2087,14 @@ pub fn basic_drag_widget(
         parent: Some(parent),
         ..default()
     });
-    let value = drag_value(pico, scale, value, drag_index, Some(char_input_events));
+    let value = drag_value(
+        pico,
+        scale,
+        value,
+        drag_index,
+        Some(char_input_events),
+        CharFilter::Numeric,
+    );
     if relative {
         // Show relative value while dragging drag
         if let Some(state) = pico.get_state_mut(&drag_index) {
@@ -234,6 +2106,77 @@ pub fn basic_drag_widget(
     value
 }
 
+// -------------------------
+// Stepper example widget
+// -------------------------
+
+/// A value field between - and + buttons filling `parent`. The buttons change the value by `step`,
+/// dragging the field changes it by a step per 1% of the window. The value is clamped to `range`.
+/// Returns the field index.
+pub fn stepper(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    value: &mut f32,
+    step: f32,
+    range: RangeInclusive<f32>,
+) -> ItemIndex {
+    let parent_bbox = pico.get(&parent).get_bbox();
+    let parent_px = pico
+        .uv_scale_to_px(parent_bbox.zw() - parent_bbox.xy())
+        .max(Vec2::ONE);
+    let margin_px = 2.0;
+    let bg = Color::rgb(0.2, 0.2, 0.2);
+    // Square buttons, the height of the parent
+    let step_button = |pico: &mut Pico, text: &str| {
+        button(
+            pico,
+            PicoItem {
+                text: text.to_string(),
+                width: Val::Px(parent_px.y),
+                height: Val::Percent(100.0),
+                style: ItemStyle {
+                    corner_radius: Val::Percent(10.0),
+                    background_color: bg,
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent: Some(parent),
+                ..default()
+            },
+        )
+    };
+
+    let _guard = pico.hstack(Val::Px(0.0), Val::Px(margin_px), false, &parent);
+    let minus = step_button(pico, "-");
+    // Leave room for the + button and the margin before it
+    let plus_size = (parent_px.y + margin_px) / parent_px.x;
+    let field = pico.add(PicoItem {
+        text: format!("{:.2}", *value),
+        height: Val::Percent(100.0),
+        uv_size: vec2(pico.remaining_stack_space() - plus_size, 0.0),
+        style: ItemStyle {
+            corner_radius: Val::Percent(10.0),
+            background_color: bg,
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        parent: Some(parent),
+        ..default()
+    });
+    let plus = step_button(pico, "+");
+
+    *value = drag_value(pico, step * 100.0, *value, field, None, CharFilter::Numeric);
+    if pico.clicked(&minus) {
+        *value -= step;
+    }
+    if pico.clicked(&plus) {
+        *value += step;
+    }
+    *value = value.clamp(*range.start(), *range.end());
+    pico.get_mut(&field).text = format!("{:.2}", *value);
+    field
+}
+
 // --------------------------
 // Example scroll area widget
 // --------------------------
@@ -262,7 +2205,6 @@ impl ScrollAreaWidget {
         id: u64,
         parent: ItemIndex,
         initial_scroll_position: Option<i32>,
-        mouse_wheel_events: &mut EventReader<MouseWheel>,
     ) -> ScrollAreaWidget {
         let mut items = Vec::new();
         let content_area;
@@ -282,15 +2224,20 @@ impl ScrollAreaWidget {
             anchor: Anchor::TopLeft,
             anchor_parent: Anchor::TopLeft,
             parent: Some(parent),
+            // Manually set id so the scroll position is kept if the area moves, like when it is nested
+            // in another scroll area. The handle uses `id`.
+            spatial_id: Some(child_id(id, "scroll_bar")),
             ..default()
         });
 
+        let key = persistent_key("scroll_area", id);
+        let restored = pico.get_persistent::<i32>(&key).or(initial_scroll_position);
         if let Some(state) = pico.get_state_mut(&scroll_widget) {
             if state.storage.is_none() {
-                if let Some(initial_scroll_position) = initial_scroll_position {
-                    scroll_position = initial_scroll_position;
+                if let Some(restored) = restored {
+                    scroll_position = restored;
                     fscroll_position = scroll_position as f32 / scroll_range as f32;
-                    state.storage = Some(Box::new((fscroll_position, scroll_position)));
+                    state.storage = Some(Box::new((scroll_position, fscroll_position)));
                 }
             }
         }
@@ -371,16 +2318,15 @@ impl ScrollAreaWidget {
                                 fscroll_updated = true;
                             };
                         }
-                        if pico.hovered(&scroll_widget) {
-                            for event in mouse_wheel_events.read() {
-                                scroll_position = (scroll_position
-                                    + match event.unit {
-                                        MouseScrollUnit::Line => -event.y,
-                                        MouseScrollUnit::Pixel => -event.y / 10.0, //TODO: idk about scale
-                                    } as i32)
-                                    .clamp(0, scroll_range);
-                                scroll_updated = true;
-                            }
+                        let scroll_delta = pico.consume_scroll_delta(
+                            &scroll_widget,
+                            scroll_position as f32,
+                            (scroll_range - scroll_position) as f32,
+                        );
+                        if scroll_delta.y != 0.0 {
+                            scroll_position =
+                                (scroll_position - scroll_delta.y as i32).clamp(0, scroll_range);
+                            scroll_updated = true;
                         }
                         let handle_abs_pos = (fscroll_position * lane_height) * 100.0;
                         let _guard = pico.stack_bypass();
@@ -426,6 +2372,7 @@ impl ScrollAreaWidget {
                 if let Some(state) = pico.get_state_mut(&scroll_widget) {
                     state.storage = Some(Box::new((scroll_position, fscroll_position)));
                 }
+                pico.set_persistent(&key, &scroll_position);
             }
         }
         ScrollAreaWidget {
@@ -443,3 +2390,597 @@ impl ScrollAreaWidget {
         }
     }
 }
+
+// --------------------------
+// Example settings panel widget
+// --------------------------
+
+/// Lays out labeled controls in rows with the labels aligned in a column.
+/// Only `visible_rows` are shown at a time, when there are more a scroll handle is shown on the right.
+/// Each control returns true when its value was changed, `changed` is true if any were.
+pub struct SettingsPanel<'a> {
+    pub pico: &'a mut Pico,
+    pub panel: ItemIndex,
+    pub content_area: ItemIndex,
+    pub changed: bool,
+    id: u64,
+    visible_rows: usize,
+    row_count: usize,
+    scroll: usize,
+}
+
+/// `id` must be consistent across frames so the scroll position persists.
+pub fn settings_panel(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    visible_rows: usize,
+    id: u64,
+) -> SettingsPanel<'_> {
+    let panel = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        style: ItemStyle {
+            background_color: Color::rgb(0.1, 0.1, 0.1),
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        parent: Some(parent),
+        spatial_id: Some(id), // Manually set id
+        ..default()
+    });
+
+    // Row count is from last frame since rows are added after this.
+    let mut scroll = 0.0;
+    let mut last_row_count = 0;
+    if let Some(state) = pico.get_state(&panel) {
+        if let Some(storage) = &state.storage {
+            if let Some(data) = storage.downcast_ref::<(f32, usize)>() {
+                (scroll, last_row_count) = *data;
+            }
+        }
+    }
+    let scroll_range = last_row_count.saturating_sub(visible_rows);
+    let mut content_width = 100.0;
+    if scroll_range > 0 {
+        let handle_id = child_id(id, "scroll_handle");
+        let handle_height = 100.0 * visible_rows as f32 / last_row_count as f32;
+        let bbox = pico.get(&panel).get_bbox();
+        let lane_height = (bbox.w - bbox.y) * (1.0 - handle_height / 100.0);
+        if let Some(state) = pico.state.get(&handle_id) {
+            if let Some(drag) = state.drag {
+                scroll = (scroll + drag.delta().y / lane_height).clamp(0.0, 1.0);
+            }
+        }
+        let _guard = pico.stack_bypass();
+        pico.add(PicoItem {
+            y: Val::Percent(scroll * (100.0 - handle_height)),
+            width: Val::Vh(1.0),
+            height: Val::Percent(handle_height),
+            style: ItemStyle {
+                corner_radius: Val::Percent(50.0),
+                background_color: Color::rgb(0.4, 0.4, 0.4),
+                ..default()
+            },
+            anchor: Anchor::TopRight,
+            anchor_parent: Anchor::TopRight,
+            parent: Some(panel),
+            spatial_id: Some(handle_id), // Manually set id
+            ..default()
+        });
+        content_width = 95.0;
+    } else {
+        scroll = 0.0;
+    }
+
+    let content_area = pico.add(PicoItem {
+        width: Val::Percent(content_width),
+        height: Val::Percent(100.0),
+        anchor: Anchor::TopLeft,
+        parent: Some(panel),
+        ..default()
+    });
+
+    if let Some(state) = pico.get_state_mut(&panel) {
+        state.storage = Some(Box::new((scroll, last_row_count)));
+    }
+
+    SettingsPanel {
+        pico,
+        panel,
+        content_area,
+        changed: false,
+        id,
+        visible_rows,
+        row_count: 0,
+        scroll: (scroll * scroll_range as f32).round() as usize,
+    }
+}
+
+impl<'a> SettingsPanel<'a> {
+    /// Returns None if the row is scrolled out of view
+    fn next_row(&mut self) -> Option<ItemIndex> {
+        let row = self.row_count;
+        self.row_count += 1;
+        if row < self.scroll || row >= self.scroll + self.visible_rows {
+            return None;
+        }
+        let _guard = self.pico.stack_bypass();
+        Some(self.pico.add(PicoItem {
+            y: Val::Percent(100.0 * (row - self.scroll) as f32 / self.visible_rows as f32),
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0 / self.visible_rows as f32),
+            anchor: Anchor::TopLeft,
+            parent: Some(self.content_area),
+            ..default()
+        }))
+    }
+
+    fn row_id(&self) -> u64 {
+        child_id(self.id, ("row", self.row_count))
+    }
+
+    /// Adds the label on the left and returns the area on the right for the control.
+    fn labeled_row(&mut self, label: &str) -> Option<ItemIndex> {
+        let row = self.next_row()?;
+        let _guard = self.pico.stack_bypass();
+        self.pico.add(PicoItem {
+            text: label.to_string(),
+            x: Val::Percent(4.0),
+            width: Val::Percent(40.0),
+            height: Val::Percent(100.0),
+            style: ItemStyle {
+                anchor_text: Anchor::CenterLeft,
+                justify: JustifyText::Left,
+                ..default()
+            },
+            anchor: Anchor::TopLeft,
+            parent: Some(row),
+            ..default()
+        });
+        Some(self.pico.add(PicoItem {
+            x: Val::Percent(4.0),
+            width: Val::Percent(50.0),
+            height: Val::Percent(60.0),
+            anchor: Anchor::CenterRight,
+            anchor_parent: Anchor::CenterRight,
+            parent: Some(row),
+            ..default()
+        }))
+    }
+
+    pub fn section(&mut self, title: &str) {
+        let Some(row) = self.next_row() else {
+            return;
+        };
+        let _guard = self.pico.stack_bypass();
+        self.pico.add(PicoItem {
+            text: title.to_string(),
+            x: Val::Percent(2.0),
+            width: Val::Percent(96.0),
+            height: Val::Percent(100.0),
+            style: ItemStyle {
+                font_size: Val::Vh(2.5),
+                anchor_text: Anchor::CenterLeft,
+                justify: JustifyText::Left,
+                ..default()
+            },
+            anchor: Anchor::TopLeft,
+            parent: Some(row),
+            ..default()
+        });
+    }
+
+    pub fn toggle(&mut self, label: &str, value: &mut bool) -> bool {
+        let id = self.row_id();
+        let Some(control) = self.labeled_row(label) else {
+            return false;
+        };
+        let _guard = self.pico.stack_bypass();
+        let bbox = self.pico.get(&control).get_bbox();
+        let height_px = self.pico.uv_scale_to_px(bbox.zw() - bbox.xy()).y;
+        let holder = self.pico.add(PicoItem {
+            width: Val::Px(height_px * 2.0),
+            height: Val::Percent(100.0),
+            anchor: Anchor::CenterRight,
+            anchor_parent: Anchor::CenterRight,
+            parent: Some(control),
+            ..default()
+        });
+        let before = *value;
+        switch(self.pico, holder, value, id);
+        let changed = before != *value;
+        self.changed |= changed;
+        changed
+    }
+
+    pub fn slider(&mut self, label: &str, value: &mut f32, min: f32, max: f32) -> bool {
+        let Some(control) = self.labeled_row(label) else {
+            return false;
+        };
+        let _guard = self.pico.stack_bypass();
+        let before = *value;
+        slider(
+            self.pico,
+            PicoItem {
+                x: Val::Percent(5.0),
+                width: Val::Percent(90.0),
+                height: Val::Percent(25.0),
+                style: ItemStyle {
+                    corner_radius: Val::Percent(50.0),
+                    background_color: Color::rgb(0.25, 0.25, 0.25),
+                    hit_padding: Val::Px(6.0),
+                    ..default()
+                },
+                anchor: Anchor::CenterLeft,
+                anchor_parent: Anchor::CenterLeft,
+                parent: Some(control),
+                ..default()
+            },
+            value,
+            min,
+            max,
+        );
+        let changed = before != *value;
+        self.changed |= changed;
+        changed
+    }
+
+    pub fn dropdown(&mut self, label: &str, options: &[&str], selected: &mut usize) -> bool {
+        let id = self.row_id();
+        let Some(control) = self.labeled_row(label) else {
+            return false;
+        };
+        let _guard = self.pico.stack_bypass();
+        let changed = dropdown(
+            self.pico,
+            PicoItem {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                style: ItemStyle {
+                    corner_radius: Val::Percent(10.0),
+                    background_color: Color::rgb(0.25, 0.25, 0.25),
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent: Some(control),
+                ..default()
+            },
+            options,
+            selected,
+            id,
+        );
+        self.changed |= changed;
+        changed
+    }
+}
+
+impl<'a> Drop for SettingsPanel<'a> {
+    fn drop(&mut self) {
+        // Keep the row count for the scroll range next frame
+        let row_count = self.row_count;
+        if let Some(state) = self.pico.get_state_mut(&self.panel) {
+            if let Some(storage) = &mut state.storage {
+                if let Some(data) = storage.downcast_mut::<(f32, usize)>() {
+                    data.1 = row_count;
+                }
+            }
+        }
+    }
+}
+
+// -------------------------
+// File picker example widget
+// -------------------------
+
+/// A directory entry listed by `file_picker()`. The caller reads the filesystem (or any other source)
+/// so the picker itself is platform-agnostic.
+#[derive(Clone, Debug)]
+pub struct FileEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilePickerResult {
+    /// A folder was double-clicked or opened with enter, supply its entries next frame.
+    OpenFolder(PathBuf),
+    /// A file was double-clicked, or selected and confirmed.
+    Picked(PathBuf),
+    Cancelled,
+}
+
+#[derive(Default)]
+struct FilePickerState {
+    selected: Option<usize>,
+    scroll: usize,
+}
+
+/// A modal dialog listing `entries`. Folders are opened by double-clicking them, files are picked by
+/// double-clicking or selecting and pressing Open. Up and down arrows move the selection, enter opens
+/// or picks it, and escape cancels. Returns None while the dialog is still open.
+/// `id` must be consistent across frames so the selection persists.
+pub fn file_picker(pico: &mut Pico, id: u64, entries: &[FileEntry]) -> Option<FilePickerResult> {
+    // Interactable so clicks don't fall through to items behind the dialog
+    let backdrop = pico.add(PicoItem {
+        uv_size: Vec2::ONE,
+        style: ItemStyle {
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+            ..default()
+        },
+        anchor: Anchor::TopLeft,
+        depth: Some(0.85),
+        spatial_id: Some(id), // Manually set id
+        ..default()
+    });
+    let mut state = FilePickerState::default();
+    if let Some(state_item) = pico.get_state_mut(&backdrop) {
+        if let Some(storage) = &mut state_item.storage {
+            if let Some(data) = storage.downcast_mut::<FilePickerState>() {
+                state = std::mem::take(data);
+            }
+        }
+    }
+    state.selected = state.selected.filter(|i| *i < entries.len());
+
+    let dialog = pico.add(PicoItem {
+        uv_position: vec2(0.5, 0.5),
+        width: Val::Vw(50.0),
+        height: Val::Vh(60.0),
+        style: ItemStyle {
+            corner_radius: Val::Px(6.0),
+            background_color: Color::rgb(0.12, 0.12, 0.12),
+            ..default()
+        },
+        parent: Some(backdrop),
+        ..default()
+    });
+
+    let _guard = pico.stack_bypass();
+    let row_height = 4.0;
+    let list_height = 80.0;
+    let visible_rows = (list_height / row_height) as usize;
+    let list = pico.add(PicoItem {
+        y: Val::Percent(5.0),
+        width: Val::Percent(95.0),
+        height: Val::Percent(list_height),
+        style: ItemStyle {
+            background_color: Color::rgb(0.08, 0.08, 0.08),
+            ..default()
+        },
+        anchor: Anchor::TopCenter,
+        anchor_parent: Anchor::TopCenter,
+        parent: Some(dialog),
+        ..default()
+    });
+
+    let mut result = None;
+    let mut activate = None;
+
+    if let Some(selected) = state.selected {
+        if pico.key_just_pressed(KeyCode::ArrowDown) && selected + 1 < entries.len() {
+            state.selected = Some(selected + 1);
+        }
+        if pico.key_just_pressed(KeyCode::ArrowUp) && selected > 0 {
+            state.selected = Some(selected - 1);
+        }
+        if pico.key_just_pressed(KeyCode::Enter) {
+            activate = state.selected;
+        }
+    } else if pico.key_just_pressed(KeyCode::ArrowDown) && !entries.is_empty() {
+        state.selected = Some(0);
+    }
+    if pico.key_just_pressed(KeyCode::Escape) {
+        result = Some(FilePickerResult::Cancelled);
+    }
+
+    // Keep the selection in view
+    if let Some(selected) = state.selected {
+        if selected < state.scroll {
+            state.scroll = selected;
+        } else if selected >= state.scroll + visible_rows {
+            state.scroll = selected + 1 - visible_rows;
+        }
+    }
+    state.scroll = state.scroll.min(entries.len().saturating_sub(visible_rows));
+
+    for (i, entry) in entries
+        .iter()
+        .enumerate()
+        .skip(state.scroll)
+        .take(visible_rows)
+    {
+        let row = button(
+            pico,
+            PicoItem {
+                text: format!("     {}", entry.name),
+                y: Val::Percent(100.0 * (i - state.scroll) as f32 / visible_rows as f32),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0 / visible_rows as f32),
+                style: ItemStyle {
+                    background_color: if state.selected == Some(i) {
+                        Color::rgb(0.2, 0.3, 0.5)
+                    } else {
+                        Color::NONE
+                    },
+                    anchor_text: Anchor::CenterLeft,
+                    justify: JustifyText::Left,
+                    ..default()
+                },
+                anchor: Anchor::TopLeft,
+                parent: Some(list),
+                ..default()
+            },
+        );
+        // Folder and file icons
+        pico.add(PicoItem {
+            x: Val::Vh(0.8),
+            width: Val::Vh(1.6),
+            height: Val::Percent(if entry.is_dir { 50.0 } else { 60.0 }),
+            style: ItemStyle {
+                corner_radius: Val::Px(2.0),
+                background_color: if entry.is_dir {
+                    Color::rgb(0.9, 0.7, 0.3)
+                } else {
+                    Color::rgb(0.7, 0.7, 0.7)
+                },
+                ..default()
+            },
+            anchor: Anchor::CenterLeft,
+            anchor_parent: Anchor::CenterLeft,
+            parent: Some(row),
+            interactable: false,
+            ..default()
+        });
+        if pico.clicked(&row) {
+            state.selected = Some(i);
+        }
+        if pico.double_clicked(&row) {
+            activate = Some(i);
+        }
+    }
+
+    let footer_button = |text: &str, x: f32| PicoItem {
+        text: text.to_string(),
+        x: Val::Percent(x),
+        y: Val::Percent(2.5),
+        width: Val::Percent(20.0),
+        height: Val::Percent(8.0),
+        style: ItemStyle {
+            corner_radius: Val::Px(4.0),
+            background_color: Color::rgb(0.25, 0.25, 0.25),
+            ..default()
+        },
+        anchor: Anchor::BottomRight,
+        anchor_parent: Anchor::BottomRight,
+        parent: Some(dialog),
+        ..default()
+    };
+    let open = button(pico, footer_button("Open", 2.5));
+    let cancel = button(pico, footer_button("Cancel", 25.0));
+    if pico.clicked(&open) {
+        activate = state.selected;
+    }
+    if pico.clicked(&cancel) {
+        result = Some(FilePickerResult::Cancelled);
+    }
+
+    if let Some(entry) = activate.and_then(|i| entries.get(i)) {
+        result = Some(if entry.is_dir {
+            FilePickerResult::OpenFolder(entry.path.clone())
+        } else {
+            FilePickerResult::Picked(entry.path.clone())
+        });
+        if entry.is_dir {
+            // New entries next frame
+            state = FilePickerState::default();
+        }
+    }
+
+    if let Some(state_item) = pico.get_state_mut(&backdrop) {
+        state_item.storage = Some(Box::new(state));
+    }
+    result
+}
+
+// -------------------------
+// Sortable table header example widget
+// -------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SortColumn {
+    pub column: usize,
+    pub direction: SortDirection,
+}
+
+/// A row of column headers filling `parent`. Clicking a header sorts by that column ascending,
+/// clicking it again toggles the direction. If `multi_sort` is true shift+click adds the column
+/// as another sort key (or toggles it if it's already one) instead of replacing the sort.
+/// Returns the sort keys in priority order, the caller reorders its rows with them. For large
+/// tables sort first and only add the visible rows, see `ScrollAreaWidget`.
+/// `id` must be consistent across frames so the sort persists.
+pub fn table_header(
+    pico: &mut Pico,
+    parent: ItemIndex,
+    columns: &[&str],
+    multi_sort: bool,
+    id: u64,
+) -> Vec<SortColumn> {
+    let row = pico.add(PicoItem {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        anchor: Anchor::TopLeft,
+        anchor_parent: Anchor::TopLeft,
+        spatial_id: Some(id), // Manually set id
+        parent: Some(parent),
+        ..default()
+    });
+    let mut sort = Vec::new();
+    if let Some(state) = pico.get_state(&row) {
+        if let Some(storage) = &state.storage {
+            if let Some(data) = storage.downcast_ref::<Vec<SortColumn>>() {
+                sort = data.clone();
+            }
+        }
+    }
+    {
+        let _guard = pico.hstack(Val::Px(0.0), Val::Px(0.0), false, &row);
+        for (i, column) in columns.iter().enumerate() {
+            let header = button(
+                pico,
+                PicoItem {
+                    text: column.to_string(),
+                    width: Val::Percent(100.0 / columns.len() as f32),
+                    height: Val::Percent(100.0),
+                    style: ItemStyle {
+                        background_color: Color::rgba(0.1, 0.1, 0.1, 1.0),
+                        border_width: Val::Px(1.0),
+                        border_color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+                        ..default()
+                    },
+                    anchor: Anchor::TopLeft,
+                    parent: Some(row),
+                    ..default()
+                },
+            );
+            let shift_click = multi_sort
+                && pico.clicked_with_mods(&header, MouseButton::Left, &[KeyCode::ShiftLeft], false);
+            if pico.clicked(&header) {
+                let existing = sort.iter().position(|s| s.column == i);
+                let direction = match existing.map(|p| sort[p].direction) {
+                    Some(SortDirection::Ascending) => SortDirection::Descending,
+                    _ => SortDirection::Ascending,
+                };
+                if !shift_click {
+                    sort.clear();
+                }
+                match existing.filter(|_| shift_click) {
+                    Some(p) => sort[p].direction = direction,
+                    None => sort.push(SortColumn {
+                        column: i,
+                        direction,
+                    }),
+                }
+            }
+            if let Some(s) = sort.iter().find(|s| s.column == i) {
+                let bbox = pico.get(&header).get_bbox();
+                let header_px = pico.uv_scale_to_px(bbox.zw() - bbox.xy());
+                let t = match s.direction {
+                    SortDirection::Ascending => -1.0,
+                    SortDirection::Descending => 1.0,
+                };
+                disclosure_triangle(pico, header, header_px.x - header_px.y, t);
+            }
+        }
+    }
+    if let Some(state) = pico.get_state_mut(&row) {
+        state.storage = Some(Box::new(sort.clone()));
+    }
+    sort
+}